@@ -1,25 +1,176 @@
 use std::{
+    future::Future,
     pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     task::{Context, Poll},
+    time::Duration,
 };
 
-use futures_util::Sink;
+use futures_channel::oneshot;
+use futures_core::{ready, Stream};
+use futures_util::{
+    future::{self, FutureExt, Shared as SharedFuture},
+    stream, Sink, StreamExt,
+};
 
 use crate::{
-    channel::Sender,
-    futures::{MessageFuture, QueueFuture, Wait},
-    Envelope, Error, Message, Wrap,
+    channel::{ChannelError, Disconnected, Sender},
+    futures::{MessageFuture, QueueFuture, ResponseFuture, Wait},
+    timer::{default_timer, Timer},
+    Envelope, Error, Message, Priority, Recipient, StreamMessage, TrySendError, Wrap,
 };
 
+/// State shared between the addresses of a mailbox.
+struct Shared {
+    /// The number of strong addresses.
+    strong: AtomicUsize,
+    /// Whether the actor has stopped receiving messages.
+    stopped: AtomicBool,
+    /// The number of queued messages.
+    len: AtomicUsize,
+    /// Dropped once the mailbox is dropped.
+    closed_tx: Mutex<Option<oneshot::Sender<()>>>,
+    /// Resolves once the mailbox is dropped.
+    closed_rx: SharedFuture<oneshot::Receiver<()>>,
+    /// The timer of the runner which receives from the mailbox.
+    timer: Mutex<Option<Arc<dyn Timer>>>,
+    /// Whether the runner which receives from the mailbox records metrics, in which case
+    /// messages are timestamped when they are queued.
+    metrics: AtomicBool,
+}
+
+impl std::fmt::Debug for Shared {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shared")
+            .field("strong", &self.strong)
+            .field("stopped", &self.stopped)
+            .field("len", &self.len)
+            .field("metrics", &self.metrics)
+            .finish_non_exhaustive()
+    }
+}
+
 /// An address which can be used to send messages to a mailbox.
+///
+/// The mailbox is closed once every address to it has been dropped.
 #[derive(Debug)]
 pub struct Address<T: Message> {
     sender: Sender<T>,
+    /// A message passed to the [`Sink`], which is queued once its lane of the mailbox is
+    /// ready. Only accessed mutably, so it is never locked, and keeps the address `Sync`.
+    pending: Mutex<Option<Envelope<T>>>,
+    shared: Arc<Shared>,
 }
 
 impl<T: Message> Address<T> {
     pub(crate) fn new(sender: Sender<T>) -> Self {
-        Self { sender }
+        let (closed_tx, closed_rx) = oneshot::channel();
+        Self {
+            sender,
+            pending: Mutex::new(None),
+            shared: Arc::new(Shared {
+                strong: AtomicUsize::new(1),
+                stopped: AtomicBool::new(false),
+                len: AtomicUsize::new(0),
+                closed_tx: Mutex::new(Some(closed_tx)),
+                closed_rx: closed_rx.shared(),
+                timer: Mutex::new(None),
+                metrics: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Returns a weak address which does not keep the mailbox open.
+    ///
+    /// This is useful for registries and self-references, which should not prevent an actor
+    /// from stopping once every other address has been dropped.
+    pub fn downgrade(&self) -> WeakAddress<T> {
+        WeakAddress {
+            sender: self.sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Returns the timer of the runner which receives from the mailbox, or the
+    /// [`default_timer`] if it does not have one.
+    pub(crate) fn timer(&self) -> Option<Arc<dyn Timer>> {
+        self.shared
+            .timer
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+            .or_else(default_timer)
+    }
+
+    /// Returns the error to report when a message can not be sent.
+    pub(crate) fn send_error(&self) -> Error {
+        if self.shared.stopped.load(Ordering::Acquire) {
+            Error::Stopped
+        } else {
+            Error::Disconnected
+        }
+    }
+
+    pub(crate) fn poll_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+        priority: Priority,
+    ) -> Poll<Result<(), Disconnected>> {
+        self.sender.poll_ready(cx, priority)
+    }
+
+    /// Timestamps a message which is about to be queued, if the runner records metrics.
+    fn stamp(&self, envelope: &mut Envelope<T>) {
+        if self.shared.metrics.load(Ordering::Relaxed) {
+            envelope.stamp();
+        }
+    }
+
+    pub(crate) fn try_send_envelope(
+        &mut self,
+        mut envelope: Envelope<T>,
+    ) -> Result<(), ChannelError<Envelope<T>>> {
+        self.stamp(&mut envelope);
+        // Count the message before it is queued, so the mailbox never receives an uncounted
+        // message.
+        self.shared.len.fetch_add(1, Ordering::AcqRel);
+        self.sender.try_send(envelope).inspect_err(|_| {
+            self.shared.len.fetch_sub(1, Ordering::AcqRel);
+        })
+    }
+
+    /// Attempts to queue a message without waiting, returning the message back if it could
+    /// not be queued.
+    ///
+    /// Like the underlying channel, every address is guaranteed a slot in the mailbox, so a
+    /// full mailbox may accept one extra message from each address.
+    pub fn try_queue(&mut self, msg: T) -> Result<(), TrySendError<T>> {
+        self.try_send_envelope(Envelope::new(msg))
+            .map_err(|err| self.try_send_error(err))
+    }
+
+    /// Attempts to send a message without waiting, returning a future which resolves to the
+    /// response or the message back if it could not be queued.
+    ///
+    /// See [`Address::try_queue`].
+    pub fn try_send(&mut self, msg: T) -> Result<ResponseFuture<T>, TrySendError<T>> {
+        let (envelope, response) = Envelope::new_with_response(msg);
+        self.try_send_envelope(envelope)
+            .map(|_| response)
+            .map_err(|err| self.try_send_error(err))
+    }
+
+    fn try_send_error(&self, err: ChannelError<Envelope<T>>) -> TrySendError<T> {
+        match err {
+            ChannelError::Full(envelope) => TrySendError::Full(envelope.into_message()),
+            ChannelError::Disconnected(envelope) => match self.send_error() {
+                Error::Stopped => TrySendError::Stopped(envelope.into_message()),
+                _ => TrySendError::Disconnected(envelope.into_message()),
+            },
+        }
     }
 
     /// Closes the mailbox with this address.
@@ -27,9 +178,15 @@ impl<T: Message> Address<T> {
         self.sender.close();
     }
 
-    /// Returns whether the mailbox is connected.
+    /// Returns whether the mailbox is connected, ie. whether it is still receiving messages.
     pub fn is_connected(&self) -> bool {
-        self.sender.is_closed()
+        !self.sender.is_closed()
+    }
+
+    /// Returns a future which resolves once the mailbox has been dropped, eg. once the actor
+    /// has stopped and its task has finished.
+    pub fn closed(&self) -> impl Future<Output = ()> + Send + 'static {
+        self.shared.closed_rx.clone().map(|_| ())
     }
 
     /// Sends a message and waits for a response.
@@ -41,50 +198,261 @@ impl<T: Message> Address<T> {
         T::unwrap_return(self.wait(msg.into()).await?)
     }
 
+    /// Sends a message and waits for a response, failing with [`Error::Timeout`] if a response
+    /// is not received within `timeout`.
+    ///
+    /// The timeout uses the timer of the [`Runner`](crate::Runner) which receives from the
+    /// mailbox, or the [`default_timer`] if it does not have one. If neither is available,
+    /// the response is waited for without a timeout.
+    pub async fn send_timeout<U>(&self, msg: U, timeout: Duration) -> Result<U::Return, Error>
+    where
+        T: Wrap<U>,
+        U: Message,
+    {
+        T::unwrap_return(self.wait(msg.into()).timeout(timeout).await?)
+    }
+
+    /// Sends a message with the given priority and waits for a response.
+    ///
+    /// The priority overrides the default priority of the message, see [`Priority`].
+    pub async fn send_with_priority<U>(
+        &self,
+        msg: U,
+        priority: Priority,
+    ) -> Result<U::Return, Error>
+    where
+        T: Wrap<U>,
+        U: Message,
+    {
+        let (envelope, response) = Envelope::new_with_response(msg.into());
+        let fut = MessageFuture::new(
+            QueueFuture::new(self.clone(), envelope.with_priority(priority)),
+            response,
+        );
+
+        T::unwrap_return(fut.await?)
+    }
+
     /// Returns a future which resolves immediately when a message is queued.
     pub fn queue(&self, msg: T) -> QueueFuture<T> {
-        QueueFuture::new(self.sender.clone(), Envelope::new(msg))
+        QueueFuture::new(self.clone(), Envelope::new(msg))
+    }
+
+    /// Returns a future which resolves immediately when a message is queued with the given
+    /// priority.
+    ///
+    /// The priority overrides the default priority of the message, see [`Priority`].
+    pub fn queue_with_priority(&self, msg: T, priority: Priority) -> QueueFuture<T> {
+        QueueFuture::new(self.clone(), Envelope::new(msg).with_priority(priority))
+    }
+
+    /// Sends a message and returns a stream of the items of its response, see
+    /// [`StreamingHandler`](crate::StreamingHandler).
+    ///
+    /// If the message could not be handled, the stream yields a single error.
+    pub fn send_stream<U>(
+        &self,
+        msg: U,
+    ) -> impl Stream<Item = Result<U::Item, Error>> + Send + 'static
+    where
+        T: Wrap<U>,
+        U: StreamMessage,
+    {
+        self.wait(msg.into())
+            .map(|ret| T::unwrap_return(ret?))
+            .into_stream()
+            .flat_map(|res| match res {
+                Ok(items) => items.map(Ok).left_stream(),
+                Err(err) => stream::once(future::ready(Err(err))).right_stream(),
+            })
+    }
+
+    /// Returns a recipient which sends messages of type `U` to the mailbox.
+    pub fn recipient<U>(&self) -> Recipient<U>
+    where
+        T: Wrap<U>,
+        U: Message,
+    {
+        Recipient::new(self.clone())
     }
 
     /// Returns a future which will send a message and wait for a response.
     pub fn wait(&self, msg: T) -> MessageFuture<T, Wait> {
         let (envelope, response) = Envelope::new_with_response(msg);
-        MessageFuture::new(QueueFuture::new(self.sender.clone(), envelope), response)
+        MessageFuture::new(QueueFuture::new(self.clone(), envelope), response)
     }
 }
 
 impl<T: Message> Clone for Address<T> {
     fn clone(&self) -> Self {
+        self.shared.strong.fetch_add(1, Ordering::Relaxed);
+
         Self {
             sender: self.sender.clone(),
+            pending: Mutex::new(None),
+            shared: self.shared.clone(),
         }
     }
 }
 
+impl<T: Message> Drop for Address<T> {
+    fn drop(&mut self) {
+        if self.shared.strong.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.sender.close();
+        }
+    }
+}
+
+impl<T: Message> Address<T> {
+    /// Queues the message passed to the [`Sink`], if any, once its lane of the mailbox is
+    /// ready.
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if let Some(priority) = self.pending_mut().as_ref().map(|msg| msg.priority()) {
+            ready!(self.poll_ready(cx, priority)).map_err(|_| self.send_error())?;
+            let msg = self.pending_mut().take().unwrap();
+            if let Err(err) = self.try_send_envelope(msg) {
+                match err {
+                    ChannelError::Disconnected(_) => return Poll::Ready(Err(self.send_error())),
+                    ChannelError::Full(msg) => {
+                        *self.pending_mut() = Some(msg);
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn pending_mut(&mut self) -> &mut Option<Envelope<T>> {
+        self.pending
+            .get_mut()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Messages are queued in the lane of their priority, so a full lane of a priority mailbox
+/// does not hold up messages for the other lanes.
 impl<T: Message> Sink<Envelope<T>> for Address<T> {
     type Error = Error;
 
     fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Pin::new(&mut self.get_mut().sender)
-            .poll_ready(cx)
-            .map_err(|_| Error::Disconnected)
+        self.get_mut().poll_pending(cx)
     }
 
     fn start_send(self: Pin<&mut Self>, item: Envelope<T>) -> Result<(), Self::Error> {
-        Pin::new(&mut self.get_mut().sender)
-            .start_send(item)
-            .map_err(|_| Error::Disconnected)
+        let this = self.get_mut();
+        let pending = this.pending_mut();
+        debug_assert!(pending.is_none(), "`poll_ready` must be called first");
+        *pending = Some(item);
+        Ok(())
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Pin::new(&mut self.get_mut().sender)
-            .poll_flush(cx)
-            .map_err(|_| Error::Disconnected)
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        this.sender.poll_flush(cx).map_err(|_| this.send_error())
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        Pin::new(&mut self.get_mut().sender)
-            .poll_close(cx)
-            .map_err(|_| Error::Disconnected)
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        this.sender.poll_close(cx).map_err(|_| this.send_error())
+    }
+}
+
+/// A weak reference to a mailbox which does not keep it open.
+///
+/// Created with [`Address::downgrade`], and can be upgraded back to an [`Address`] as long
+/// as one still exists, similar to [`std::sync::Weak`].
+#[derive(Debug)]
+pub struct WeakAddress<T: Message> {
+    sender: Sender<T>,
+    shared: Arc<Shared>,
+}
+
+impl<T: Message> WeakAddress<T> {
+    /// Attempts to upgrade to an [`Address`], returns `None` if every address to the
+    /// mailbox has been dropped.
+    pub fn upgrade(&self) -> Option<Address<T>> {
+        let mut count = self.shared.strong.load(Ordering::Relaxed);
+        loop {
+            if count == 0 {
+                return None;
+            }
+
+            match self.shared.strong.compare_exchange_weak(
+                count,
+                count + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Some(Address {
+                        sender: self.sender.clone(),
+                        pending: Mutex::new(None),
+                        shared: self.shared.clone(),
+                    })
+                }
+                Err(actual) => count = actual,
+            }
+        }
+    }
+
+    /// Returns whether the mailbox is connected, ie. whether it is still receiving messages.
+    pub fn is_connected(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Returns the number of addresses to the mailbox.
+    pub fn strong_count(&self) -> usize {
+        self.shared.strong.load(Ordering::Acquire)
+    }
+
+    /// Marks the actor as stopped, so senders are notified with [`Error::Stopped`].
+    pub(crate) fn set_stopped(&self) {
+        self.shared.stopped.store(true, Ordering::Release);
+    }
+
+    /// Sets the timer which is used for timeouts of messages sent to the mailbox.
+    pub(crate) fn set_timer(&self, timer: Arc<dyn Timer>) {
+        *self
+            .shared
+            .timer
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(timer);
+    }
+
+    /// Timestamps messages when they are queued, so the runner can record how long they waited.
+    pub(crate) fn set_metrics(&self) {
+        self.shared.metrics.store(true, Ordering::Relaxed);
+    }
+
+    /// Resolves the futures returned by [`Address::closed`].
+    pub(crate) fn set_closed(&self) {
+        self.shared
+            .closed_tx
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+    }
+
+    /// Returns the number of queued messages.
+    pub(crate) fn queued(&self) -> usize {
+        self.shared.len.load(Ordering::Acquire)
+    }
+
+    /// Records that a queued message was received.
+    pub(crate) fn set_received(&self) {
+        self.shared.len.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<T: Message> Clone for WeakAddress<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            shared: self.shared.clone(),
+        }
     }
 }
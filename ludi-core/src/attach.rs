@@ -0,0 +1,79 @@
+use std::marker::PhantomData;
+
+use futures_core::{future::BoxFuture, stream::BoxStream, Stream};
+use futures_util::{stream, StreamExt};
+
+use crate::{Actor, Context, Dispatch, Envelope, Handler, Message};
+
+/// An actor that can handle the items of a stream, see [`Context::add_stream`].
+///
+/// Each item is handled with [`Handler::process`], without a caller to return a value to.
+pub trait StreamHandler<T: Message>: Handler<T> {
+    /// A method which is called once a stream of items of type `T` has ended.
+    ///
+    /// By default this method does nothing.
+    fn finished(&mut self, ctx: &mut Context<Self>) {
+        let _ = ctx;
+    }
+}
+
+/// An event of a stream which is attached to an actor.
+pub(crate) trait StreamEvent<A: Actor>: Send {
+    /// Handles the event with the actor.
+    fn handle<'a>(self: Box<Self>, actor: &'a mut A, ctx: &'a mut Context<A>) -> BoxFuture<'a, ()>;
+}
+
+/// An item of a stream.
+struct Item<T>(T);
+
+impl<A, T> StreamEvent<A> for Item<T>
+where
+    A: StreamHandler<T>,
+    T: Message,
+{
+    fn handle<'a>(self: Box<Self>, actor: &'a mut A, ctx: &'a mut Context<A>) -> BoxFuture<'a, ()> {
+        Box::pin(actor.process(self.0, ctx, |_| {}))
+    }
+}
+
+/// The end of a stream.
+struct Finished<T>(PhantomData<fn() -> T>);
+
+impl<A, T> StreamEvent<A> for Finished<T>
+where
+    A: StreamHandler<T>,
+    T: Message,
+{
+    fn handle<'a>(self: Box<Self>, actor: &'a mut A, ctx: &'a mut Context<A>) -> BoxFuture<'a, ()> {
+        actor.finished(ctx);
+        Box::pin(async {})
+    }
+}
+
+/// A message from a mailbox, see [`MailboxSet`](crate::MailboxSet).
+pub(crate) struct Mail<T: Message>(pub(crate) Envelope<T>);
+
+impl<A, T> StreamEvent<A> for Mail<T>
+where
+    A: Actor,
+    T: Dispatch<A>,
+{
+    fn handle<'a>(self: Box<Self>, actor: &'a mut A, ctx: &'a mut Context<A>) -> BoxFuture<'a, ()> {
+        Box::pin(crate::dispatch(actor, self.0, ctx))
+    }
+}
+
+/// Returns a stream of the events of a stream which is attached to an actor.
+pub(crate) fn events<A, S>(stream: S) -> BoxStream<'static, Box<dyn StreamEvent<A>>>
+where
+    A: StreamHandler<S::Item> + 'static,
+    S: Stream + Send + 'static,
+    S::Item: Message,
+{
+    stream
+        .map(|item| Box::new(Item(item)) as Box<dyn StreamEvent<A>>)
+        .chain(stream::once(async {
+            Box::new(Finished::<S::Item>(PhantomData)) as Box<dyn StreamEvent<A>>
+        }))
+        .boxed()
+}
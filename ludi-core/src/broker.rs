@@ -0,0 +1,199 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use futures_core::future::BoxFuture;
+use futures_util::{
+    future::{self, join_all},
+    FutureExt,
+};
+
+use crate::{Error, Message, WeakAddress, Wrap};
+
+/// A handle which can receive messages published to a [`Topic`].
+pub trait Subscriber<M: Message>: Send + Sync + 'static {
+    /// Queues a message to the subscriber, returns a future which resolves once the message
+    /// is queued.
+    fn deliver(&self, msg: M) -> BoxFuture<'static, Result<(), Error>>;
+}
+
+/// A weak address does not keep the actor running, once every [`Address`](crate::Address) has
+/// been dropped the subscription is removed on the next publish.
+impl<T, M> Subscriber<M> for WeakAddress<T>
+where
+    T: Wrap<M>,
+    M: Message,
+{
+    fn deliver(&self, msg: M) -> BoxFuture<'static, Result<(), Error>> {
+        match self.upgrade() {
+            Some(addr) => addr.queue(msg.into()).boxed(),
+            None => future::ready(Err(Error::Disconnected)).boxed(),
+        }
+    }
+}
+
+/// An identifier of a subscription to a [`Topic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+type Subscribers<M> = Vec<(SubscriptionId, Arc<dyn Subscriber<M>>)>;
+
+/// A topic which broadcasts messages to every subscriber.
+///
+/// A topic is a cheap handle, clones of it share the same subscribers. Subscribers which are
+/// no longer receiving messages are removed automatically when a message is published.
+pub struct Topic<M: Message> {
+    subscribers: Arc<Mutex<Subscribers<M>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl<M: Message> Default for Topic<M> {
+    fn default() -> Self {
+        Self {
+            subscribers: Default::default(),
+            next_id: Default::default(),
+        }
+    }
+}
+
+impl<M: Message> Clone for Topic<M> {
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: self.subscribers.clone(),
+            next_id: self.next_id.clone(),
+        }
+    }
+}
+
+impl<M: Message> std::fmt::Debug for Topic<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Topic")
+            .field("subscribers", &self.len())
+            .finish()
+    }
+}
+
+impl<M: Message> Topic<M> {
+    /// Creates a new topic without any subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to the topic, eg. with a [`WeakAddress`] from
+    /// [`Address::downgrade`](crate::Address::downgrade).
+    pub fn subscribe(&self, subscriber: impl Subscriber<M>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.lock().push((id, Arc::new(subscriber)));
+        id
+    }
+
+    /// Removes a subscription, returns `true` if it was subscribed.
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.lock();
+        let len = subscribers.len();
+        subscribers.retain(|(sub_id, _)| *sub_id != id);
+        subscribers.len() != len
+    }
+
+    /// Returns the number of subscribers.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the topic has no subscribers.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Subscribers<M>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<M: Message + Clone> Topic<M> {
+    /// Publishes a message to every subscriber, returns the number of subscribers it was
+    /// delivered to.
+    ///
+    /// The returned future resolves once the message is queued with every subscriber, so a
+    /// subscriber with a full mailbox applies backpressure to the publisher. Subscribers
+    /// which are disconnected or stopped, or whose addresses have all been dropped, are
+    /// unsubscribed.
+    pub async fn publish(&self, msg: M) -> usize {
+        let subscribers = self.lock().clone();
+
+        let results =
+            join_all(subscribers.iter().map(|(id, subscriber)| {
+                subscriber.deliver(msg.clone()).map(move |res| (*id, res))
+            }))
+            .await;
+
+        let mut delivered = 0;
+        let mut closed = Vec::new();
+        for (id, res) in results {
+            match res {
+                Ok(()) => delivered += 1,
+                Err(Error::Disconnected | Error::Stopped) => closed.push(id),
+                Err(_) => {}
+            }
+        }
+
+        if !closed.is_empty() {
+            self.lock().retain(|(id, _)| !closed.contains(id));
+        }
+
+        delivered
+    }
+}
+
+/// A broker which manages a [`Topic`] for every type of message.
+///
+/// A broker is a cheap handle, clones of it share the same topics.
+#[derive(Debug, Default, Clone)]
+pub struct Broker {
+    topics: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>>,
+}
+
+impl Broker {
+    /// Creates a new broker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the topic for messages of type `M`, creating it if it does not exist.
+    pub fn topic<M: Message + Clone>(&self) -> Topic<M> {
+        self.topics
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(TypeId::of::<M>())
+            .or_insert_with(|| Box::new(Topic::<M>::new()))
+            .downcast_ref::<Topic<M>>()
+            .expect("topic should be of type `M`")
+            .clone()
+    }
+
+    /// Subscribes to messages of type `M`, eg. with a [`WeakAddress`] from
+    /// [`Address::downgrade`](crate::Address::downgrade).
+    pub fn subscribe<M: Message + Clone>(&self, subscriber: impl Subscriber<M>) -> SubscriptionId {
+        self.topic::<M>().subscribe(subscriber)
+    }
+
+    /// Removes a subscription to messages of type `M`, returns `true` if it was subscribed.
+    pub fn unsubscribe<M: Message + Clone>(&self, id: SubscriptionId) -> bool {
+        self.topic::<M>().unsubscribe(id)
+    }
+
+    /// Publishes a message to every subscriber of messages of type `M`, returns the number of
+    /// subscribers it was delivered to.
+    ///
+    /// See [`Topic::publish`].
+    pub async fn publish<M: Message + Clone>(&self, msg: M) -> usize {
+        self.topic::<M>().publish(msg).await
+    }
+}
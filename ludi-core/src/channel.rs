@@ -1,44 +1,246 @@
 use std::{
+    future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
 };
 
-use futures_core::Stream;
-use futures_util::Sink;
+use futures_core::{ready, Stream};
+use futures_util::{future, Sink};
 
-use crate::{futures::ResponseFuture, Envelope, Error, Message};
+use crate::{
+    futures::ResponseFuture,
+    priority::{PriorityPolicy, PriorityReceiver},
+    Envelope, Error, Message, Priority,
+};
 
 // TODO: Support other channel implementations using conditional compilation.
-pub(crate) type OneshotSender<T> = futures_channel::oneshot::Sender<T>;
-pub(crate) type OneshotReceiver<T> = futures_channel::oneshot::Receiver<T>;
 pub(crate) type BoundedSender<T> = futures_channel::mpsc::Sender<Envelope<T>>;
 pub(crate) type BoundedReceiver<T> = futures_channel::mpsc::Receiver<Envelope<T>>;
 pub(crate) type UnboundedSender<T> = futures_channel::mpsc::UnboundedSender<Envelope<T>>;
 pub(crate) type UnboundedReceiver<T> = futures_channel::mpsc::UnboundedReceiver<Envelope<T>>;
 
 pub(crate) fn new_response<T: Message>() -> (ResponseSender<T>, ResponseFuture<T>) {
-    let (sender, receiver) = futures_channel::oneshot::channel();
+    let response = Arc::new(Response(Mutex::new(ResponseState {
+        value: None,
+        complete: false,
+        canceled: false,
+        senders: 1,
+        rx_waker: None,
+        tx_waker: None,
+    })));
+
+    (
+        ResponseSender(response.clone()),
+        ResponseFuture(Some(response)),
+    )
+}
+
+/// The state of a response, shared between its senders and its [`ResponseFuture`].
+///
+/// Unlike a oneshot channel, the state also serves as the [`Cancel`] signal of the message
+/// while it is handled, so sharing a sender does not require another allocation.
+pub(crate) struct Response<T: Message>(Mutex<ResponseState<T>>);
+
+struct ResponseState<T: Message> {
+    /// The response, from when it is sent until it is received.
+    value: Option<Result<T::Return, Error>>,
+    /// Whether a response has been sent, or every sender has been dropped.
+    complete: bool,
+    /// Whether the [`ResponseFuture`] has been dropped.
+    canceled: bool,
+    /// The number of senders.
+    senders: usize,
+    /// Woken once the response is complete.
+    rx_waker: Option<Waker>,
+    /// Woken once the response is canceled.
+    tx_waker: Option<Waker>,
+}
+
+impl<T: Message> Response<T> {
+    fn lock(&self) -> MutexGuard<'_, ResponseState<T>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Completes the response, if it has not been completed already.
+    fn complete(&self, res: Result<T::Return, Error>) {
+        let mut state = self.lock();
+        if state.complete {
+            return;
+        }
+
+        state.complete = true;
+        if !state.canceled {
+            state.value = Some(res);
+        }
+        if let Some(waker) = state.rx_waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Polls for the response, fails with [`Error::Interrupted`] if every sender has been
+    /// dropped without sending one.
+    pub(crate) fn poll_response(&self, cx: &mut Context<'_>) -> Poll<Result<T::Return, Error>> {
+        let mut state = self.lock();
+        if let Some(res) = state.value.take() {
+            return Poll::Ready(res);
+        }
+        if state.complete {
+            return Poll::Ready(Err(Error::Interrupted));
+        }
+
+        state.rx_waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
 
-    (ResponseSender(sender), ResponseFuture(receiver))
+    /// Marks the response as canceled, once its [`ResponseFuture`] is dropped.
+    pub(crate) fn cancel(&self) {
+        let mut state = self.lock();
+        state.canceled = true;
+        state.value = None;
+        if let Some(waker) = state.tx_waker.take() {
+            waker.wake();
+        }
+    }
 }
 
 /// A channel for sending a response to a message.
-pub struct ResponseSender<T: Message>(OneshotSender<T::Return>);
+pub struct ResponseSender<T: Message>(Arc<Response<T>>);
 
 impl<T> std::fmt::Debug for ResponseSender<T>
 where
     T: Message + std::fmt::Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("ResponseSender").field(&self.0).finish()
+        let state = self.0.lock();
+        f.debug_struct("ResponseSender")
+            .field("complete", &state.complete)
+            .field("canceled", &state.canceled)
+            .finish()
     }
 }
 
 impl<T: Message> ResponseSender<T> {
     /// Sends the response.
     pub fn send(self, msg: T::Return) {
-        // Ignore the error if the receiver has been dropped.
-        _ = self.0.send(msg);
+        // The response is dropped if the receiver has been dropped.
+        self.0.complete(Ok(msg));
+    }
+
+    /// Sends an error instead of a response.
+    pub fn send_err(self, err: Error) {
+        self.0.complete(Err(err));
+    }
+
+    /// Returns `true` if the sender is no longer waiting for the response.
+    pub fn is_canceled(&self) -> bool {
+        self.0.is_canceled()
+    }
+
+    /// Returns a future which completes once the sender is no longer waiting for the response.
+    pub fn cancellation(&mut self) -> impl Future<Output = ()> + Send + '_ {
+        future::poll_fn(|cx| self.0.poll_canceled(cx))
+    }
+}
+
+impl<T: Message> Drop for ResponseSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.senders -= 1;
+        if state.senders == 0 && !state.complete {
+            state.complete = true;
+            if let Some(waker) = state.rx_waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// A signal which is raised when the sender of a message is no longer waiting for a response.
+pub(crate) trait Cancel: Send + Sync {
+    fn is_canceled(&self) -> bool;
+
+    fn poll_canceled(&self, cx: &mut Context<'_>) -> Poll<()>;
+}
+
+impl<T: Message> Cancel for Response<T> {
+    fn is_canceled(&self) -> bool {
+        self.lock().canceled
+    }
+
+    fn poll_canceled(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.lock();
+        if state.canceled {
+            Poll::Ready(())
+        } else if state.complete {
+            // A response has already been sent.
+            Poll::Pending
+        } else {
+            state.tx_waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// The senders of a batch of messages, which is canceled once every sender is no longer
+/// waiting for a response.
+pub(crate) struct BatchCancel(Vec<Arc<dyn Cancel>>);
+
+impl BatchCancel {
+    pub(crate) fn new(cancels: Vec<Arc<dyn Cancel>>) -> Self {
+        Self(cancels)
+    }
+}
+
+impl Cancel for BatchCancel {
+    fn is_canceled(&self) -> bool {
+        self.0.iter().all(|cancel| cancel.is_canceled())
+    }
+
+    fn poll_canceled(&self, cx: &mut Context<'_>) -> Poll<()> {
+        // Every sender is polled, so each of them wakes the task once it is canceled.
+        let mut canceled = true;
+        for cancel in &self.0 {
+            canceled &= cancel.poll_canceled(cx).is_ready();
+        }
+
+        if canceled {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// A [`ResponseSender`] which can be shared while its message is handled.
+///
+/// Only the first response which is sent is received, the others are ignored.
+pub(crate) struct SharedSender<T: Message>(ResponseSender<T>);
+
+impl<T: Message> Clone for SharedSender<T> {
+    fn clone(&self) -> Self {
+        self.0 .0.lock().senders += 1;
+        Self(ResponseSender(self.0 .0.clone()))
+    }
+}
+
+impl<T: Message> SharedSender<T> {
+    pub(crate) fn new(sender: ResponseSender<T>) -> Self {
+        Self(sender)
+    }
+
+    /// Sends the response, if one has not been sent already.
+    pub(crate) fn send(&self, msg: T::Return) {
+        self.0 .0.complete(Ok(msg));
+    }
+
+    /// Sends an error, if a response has not been sent already.
+    pub(crate) fn send_err(&self, err: Error) {
+        self.0 .0.complete(Err(err));
+    }
+
+    pub(crate) fn cancel(&self) -> Arc<dyn Cancel> {
+        self.0 .0.clone()
     }
 }
 
@@ -54,10 +256,29 @@ pub(crate) fn new_unbounded_channel<T: Message>() -> (Sender<T>, Receiver<T>) {
     (Sender::Unbounded(sender), Receiver::Unbounded(receiver))
 }
 
+pub(crate) fn new_priority_channel<T: Message>(
+    capacity: usize,
+    lanes: usize,
+    policy: PriorityPolicy,
+) -> (Sender<T>, Receiver<T>) {
+    assert!(lanes > 0, "a priority channel must have at least one lane");
+
+    let (senders, receivers) = (0..lanes)
+        .map(|_| futures_channel::mpsc::channel(capacity))
+        .unzip();
+
+    (
+        Sender::Priority(senders),
+        Receiver::Priority(PriorityReceiver::new(receivers, policy)),
+    )
+}
+
 #[derive(Debug)]
 pub(crate) enum Sender<T: Message> {
     Bounded(BoundedSender<T>),
     Unbounded(UnboundedSender<T>),
+    /// Lanes, ordered from lowest to highest priority.
+    Priority(Vec<BoundedSender<T>>),
 }
 
 impl<T: Message> Clone for Sender<T> {
@@ -65,13 +286,14 @@ impl<T: Message> Clone for Sender<T> {
         match self {
             Self::Bounded(sender) => Self::Bounded(sender.clone()),
             Self::Unbounded(sender) => Self::Unbounded(sender.clone()),
+            Self::Priority(lanes) => Self::Priority(lanes.clone()),
         }
     }
 }
 
 pub(crate) enum ChannelError<T> {
     /// The channel is closed.
-    Disconnected,
+    Disconnected(T),
     /// The channel is full.
     Full(T),
 }
@@ -83,6 +305,7 @@ impl<T: Message> Sender<T> {
         match self {
             Self::Bounded(sender) => sender.close_channel(),
             Self::Unbounded(sender) => sender.close_channel(),
+            Self::Priority(lanes) => lanes.iter_mut().for_each(|lane| lane.close_channel()),
         }
     }
 
@@ -90,13 +313,23 @@ impl<T: Message> Sender<T> {
         match self {
             Self::Bounded(sender) => sender.is_closed(),
             Self::Unbounded(sender) => sender.is_closed(),
+            Self::Priority(lanes) => lanes.iter().any(|lane| lane.is_closed()),
         }
     }
 
-    pub(crate) fn poll_ready(&mut self, ctx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+    /// Polls whether a message with the given priority can be sent.
+    pub(crate) fn poll_ready(
+        &mut self,
+        ctx: &mut Context<'_>,
+        priority: Priority,
+    ) -> Poll<Result<(), Disconnected>> {
         match self {
             Self::Bounded(sender) => sender.poll_ready(ctx).map_err(|_| Disconnected),
             Self::Unbounded(sender) => sender.poll_ready(ctx).map_err(|_| Disconnected),
+            Self::Priority(lanes) => {
+                let lane = priority.lane(lanes.len());
+                lanes[lane].poll_ready(ctx).map_err(|_| Disconnected)
+            }
         }
     }
 
@@ -109,56 +342,51 @@ impl<T: Message> Sender<T> {
                 if e.is_full() {
                     ChannelError::Full(e.into_inner())
                 } else {
-                    ChannelError::Disconnected
+                    ChannelError::Disconnected(e.into_inner())
                 }
             }),
             Self::Unbounded(sender) => sender.unbounded_send(envelope).map_err(|e| {
                 if e.is_full() {
                     unreachable!("Unbounded channels cannot be full")
                 } else {
-                    ChannelError::Disconnected
+                    ChannelError::Disconnected(e.into_inner())
                 }
             }),
-        }
-    }
-}
-
-impl<T: Message> Sink<Envelope<T>> for Sender<T> {
-    type Error = Error;
-
-    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        match self.get_mut() {
-            Sender::Bounded(sender) => sender.poll_ready(cx).map_err(|_| Error::Disconnected),
-            Sender::Unbounded(sender) => sender.poll_ready(cx).map_err(|_| Error::Disconnected),
-        }
-    }
-
-    fn start_send(self: Pin<&mut Self>, item: Envelope<T>) -> Result<(), Self::Error> {
-        match self.get_mut() {
-            Sender::Bounded(sender) => sender.start_send(item).map_err(|_| Error::Disconnected),
-            Sender::Unbounded(sender) => sender.start_send(item).map_err(|_| Error::Disconnected),
+            Self::Priority(lanes) => {
+                let lane = envelope.priority().lane(lanes.len());
+                lanes[lane].try_send(envelope).map_err(|e| {
+                    if e.is_full() {
+                        ChannelError::Full(e.into_inner())
+                    } else {
+                        ChannelError::Disconnected(e.into_inner())
+                    }
+                })
+            }
         }
     }
 
-    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        match self.get_mut() {
-            Sender::Bounded(sender) => Pin::new(sender)
-                .poll_flush(cx)
-                .map_err(|_| Error::Disconnected),
-            Sender::Unbounded(sender) => Pin::new(sender)
-                .poll_flush(cx)
-                .map_err(|_| Error::Disconnected),
+    /// Polls whether every message which was sent has been flushed to the channel.
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+        match self {
+            Self::Bounded(sender) => Pin::new(sender).poll_flush(cx).map_err(|_| Disconnected),
+            Self::Unbounded(sender) => Pin::new(sender).poll_flush(cx).map_err(|_| Disconnected),
+            // Flushing a lane waits until it has capacity again, which would hold up messages
+            // for the other lanes. A message is already in its lane once it has been sent.
+            Self::Priority(_) => Poll::Ready(Ok(())),
         }
     }
 
-    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        match self.get_mut() {
-            Sender::Bounded(sender) => Pin::new(sender)
-                .poll_close(cx)
-                .map_err(|_| Error::Disconnected),
-            Sender::Unbounded(sender) => Pin::new(sender)
-                .poll_close(cx)
-                .map_err(|_| Error::Disconnected),
+    /// Polls whether this sender has been disconnected from the channel.
+    pub(crate) fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Disconnected>> {
+        match self {
+            Self::Bounded(sender) => Pin::new(sender).poll_close(cx).map_err(|_| Disconnected),
+            Self::Unbounded(sender) => Pin::new(sender).poll_close(cx).map_err(|_| Disconnected),
+            Self::Priority(lanes) => {
+                for lane in lanes.iter_mut() {
+                    ready!(Pin::new(lane).poll_close(cx)).map_err(|_| Disconnected)?;
+                }
+                Poll::Ready(Ok(()))
+            }
         }
     }
 }
@@ -167,6 +395,26 @@ impl<T: Message> Sink<Envelope<T>> for Sender<T> {
 pub(crate) enum Receiver<T: Message> {
     Bounded(BoundedReceiver<T>),
     Unbounded(UnboundedReceiver<T>),
+    Priority(PriorityReceiver<T>),
+}
+
+impl<T: Message> Receiver<T> {
+    pub(crate) fn close(&mut self) {
+        match self {
+            Self::Bounded(receiver) => receiver.close(),
+            Self::Unbounded(receiver) => receiver.close(),
+            Self::Priority(receiver) => receiver.close(),
+        }
+    }
+
+    /// Returns the next message if one is immediately available.
+    pub(crate) fn try_next(&mut self) -> Option<Envelope<T>> {
+        match self {
+            Self::Bounded(receiver) => receiver.try_recv().ok(),
+            Self::Unbounded(receiver) => receiver.try_recv().ok(),
+            Self::Priority(receiver) => receiver.try_next(),
+        }
+    }
 }
 
 impl<T: Message> Stream for Receiver<T> {
@@ -176,6 +424,7 @@ impl<T: Message> Stream for Receiver<T> {
         match self.get_mut() {
             Receiver::Bounded(receiver) => Pin::new(receiver).poll_next(cx),
             Receiver::Unbounded(receiver) => Pin::new(receiver).poll_next(cx),
+            Receiver::Priority(receiver) => Pin::new(receiver).poll_next(cx),
         }
     }
 }
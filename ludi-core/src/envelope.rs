@@ -1,4 +1,19 @@
-use crate::{channel::ResponseSender, futures::ResponseFuture, Actor, Context, Dispatch, Message};
+use std::{
+    any::{Any, TypeId},
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::Arc,
+    time::Instant,
+};
+
+use futures_util::FutureExt;
+
+use crate::{
+    channel::{BatchCancel, Cancel, ResponseSender, SharedSender},
+    futures::ResponseFuture,
+    layer::{self, Layers},
+    Actor, Context, Dispatch, Error, Message, Priority,
+};
 
 #[derive(Debug)]
 pub(crate) enum EnvelopeInner<T: Message> {
@@ -11,29 +26,187 @@ pub(crate) enum EnvelopeInner<T: Message> {
 /// An envelope containing a message and optionally a channel which can be
 /// used to return a response back to the sender.
 #[derive(Debug)]
-pub struct Envelope<T: Message>(EnvelopeInner<T>);
+pub struct Envelope<T: Message> {
+    inner: EnvelopeInner<T>,
+    priority: Priority,
+    /// The time at which the message was queued, only recorded if the actor records metrics.
+    created: Option<Instant>,
+    /// The span of the sender, which is the parent of the dispatch span.
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
 
 impl<T: Message> Envelope<T> {
     /// Create a new envelope.
     pub fn new(msg: T) -> Self {
-        Self(EnvelopeInner::NoResponse(msg))
+        Self::from_inner(EnvelopeInner::NoResponse(msg))
     }
 
     /// Create a new envelope with a channel which can be used to return
     /// a response to the sender.
     pub fn new_with_response(msg: T) -> (Self, ResponseFuture<T>) {
         let (send, recv) = ResponseFuture::new();
-        (Self(EnvelopeInner::WantsResponse(msg, send)), recv)
+        (
+            Self::from_inner(EnvelopeInner::WantsResponse(msg, send)),
+            recv,
+        )
+    }
+
+    fn from_inner(inner: EnvelopeInner<T>) -> Self {
+        let priority = match &inner {
+            EnvelopeInner::NoResponse(msg) => msg.priority(),
+            EnvelopeInner::WantsResponse(msg, _) => msg.priority(),
+        };
+
+        Self {
+            inner,
+            priority,
+            created: None,
+            #[cfg(feature = "tracing")]
+            span: tracing::Span::current(),
+        }
+    }
+
+    /// Returns the priority of the message.
+    ///
+    /// Defaults to the priority of the message type, see [`Message::priority`].
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    /// Overrides the priority of the message.
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the label of the message, see [`Message::label`].
+    pub(crate) fn label(&self) -> &'static str {
+        match &self.inner {
+            EnvelopeInner::NoResponse(msg) => msg.label(),
+            EnvelopeInner::WantsResponse(msg, _) => msg.label(),
+        }
+    }
+
+    /// Returns the time at which the message was queued, if it was recorded.
+    pub(crate) fn created(&self) -> Option<Instant> {
+        self.created
+    }
+
+    /// Records the time at which the message is queued, if it has not been recorded already.
+    pub(crate) fn stamp(&mut self) {
+        self.created.get_or_insert_with(Instant::now);
     }
 
     /// Returns `true` if the envelope has a channel which will receive a response.
     pub fn wants_response(&self) -> bool {
-        match &self.0 {
+        match &self.inner {
             EnvelopeInner::NoResponse(_) => false,
             EnvelopeInner::WantsResponse(_, _) => true,
         }
     }
 
+    /// Returns the message, dropping the response channel if there is one.
+    pub(crate) fn into_message(self) -> T {
+        match self.inner {
+            EnvelopeInner::NoResponse(msg) => msg,
+            EnvelopeInner::WantsResponse(msg, _) => msg,
+        }
+    }
+
+    /// Returns the message and the channel which returns a response to the sender, if it
+    /// wants one.
+    ///
+    /// This allows an envelope to be handled outside of an actor, eg. forwarded to another
+    /// process.
+    pub fn into_parts(self) -> (T, Option<ResponseSender<T>>) {
+        match self.inner {
+            EnvelopeInner::NoResponse(msg) => (msg, None),
+            EnvelopeInner::WantsResponse(msg, sender) => (msg, Some(sender)),
+        }
+    }
+
+    /// Returns `true` if the envelope wants a response, but the sender is no longer waiting
+    /// for it.
+    pub fn is_canceled(&self) -> bool {
+        match &self.inner {
+            EnvelopeInner::NoResponse(_) => false,
+            EnvelopeInner::WantsResponse(_, sender) => sender.is_canceled(),
+        }
+    }
+
+    /// Drops the message without handling it, returning an error to the sender if it
+    /// wants a response.
+    pub(crate) fn reject(self, err: Error) {
+        if let EnvelopeInner::WantsResponse(_, sender) = self.inner {
+            sender.send_err(err);
+        }
+    }
+
+    /// Returns `true` if the message can be dispatched concurrently, see [`Dispatch::is_concurrent`].
+    pub fn is_concurrent<A>(&self) -> bool
+    where
+        A: Actor,
+        T: Dispatch<A>,
+    {
+        match &self.inner {
+            EnvelopeInner::NoResponse(msg) => msg.is_concurrent(),
+            EnvelopeInner::WantsResponse(msg, _) => msg.is_concurrent(),
+        }
+    }
+
+    /// Returns the key of the messages this message can be handled in a batch with, see
+    /// [`Dispatch::batch_key`].
+    pub fn batch_key<A>(&self) -> Option<TypeId>
+    where
+        A: Actor,
+        T: Dispatch<A>,
+    {
+        match &self.inner {
+            EnvelopeInner::NoResponse(msg) => msg.batch_key(),
+            EnvelopeInner::WantsResponse(msg, _) => msg.batch_key(),
+        }
+    }
+
+    /// Splits a batch of envelopes into the messages and the senders of the messages which
+    /// want a response.
+    fn into_batch(batch: Vec<Self>) -> Vec<(T, Option<SharedSender<T>>)> {
+        batch
+            .into_iter()
+            .map(|env| match env.inner {
+                EnvelopeInner::NoResponse(msg) => (msg, None),
+                EnvelopeInner::WantsResponse(msg, sender) => (msg, Some(SharedSender::new(sender))),
+            })
+            .collect()
+    }
+
+    /// Returns the cancellation signal of a batch, which is only raised once every sender has
+    /// canceled, or `None` if a message of the batch does not expect a response.
+    fn batch_cancel(batch: &[(T, Option<SharedSender<T>>)]) -> Option<Arc<dyn Cancel>> {
+        let cancels = batch
+            .iter()
+            .map(|(_, sender)| sender.as_ref().map(SharedSender::cancel))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Arc::new(BatchCancel::new(cancels)))
+    }
+
+    /// Returns the span which a dispatch of the message is instrumented with.
+    #[cfg_attr(not(feature = "tracing"), allow(clippy::extra_unused_type_parameters))]
+    fn dispatch_span<A: Actor>(&self) -> DispatchSpan {
+        #[cfg(feature = "tracing")]
+        return DispatchSpan(tracing::debug_span!(
+            parent: &self.span,
+            "dispatch",
+            actor = std::any::type_name::<A>(),
+            message = self.label(),
+            wants_response = self.wants_response(),
+        ));
+
+        #[cfg(not(feature = "tracing"))]
+        DispatchSpan
+    }
+
     /// Dispatches the message and return channel to the actor for handling.
     ///
     /// # Arguments
@@ -45,16 +218,334 @@ impl<T: Message> Envelope<T> {
         A: Actor,
         T: Dispatch<A>,
     {
-        match self.0 {
-            EnvelopeInner::NoResponse(msg) => {
-                msg.dispatch(actor, ctx, move |_| {}).await;
+        let span = self.dispatch_span::<A>();
+        span.instrument(async move {
+            match self.inner {
+                EnvelopeInner::NoResponse(msg) => {
+                    layer::dispatch(msg, actor, ctx, move |_| {}).await;
+                }
+                EnvelopeInner::WantsResponse(msg, sender) => {
+                    let sender = SharedSender::new(sender);
+                    ctx.set_cancel(Some(sender.cancel()));
+                    layer::dispatch(msg, actor, ctx, move |ret| sender.send(ret)).await;
+                    ctx.set_cancel(None);
+                }
             }
-            EnvelopeInner::WantsResponse(msg, sender) => {
-                msg.dispatch(actor, ctx, move |ret| {
-                    let _ = sender.send(ret);
-                })
-                .await;
+        })
+        .await
+    }
+
+    /// Dispatches the message and return channel to the actor for concurrent handling, see
+    /// [`Dispatch::dispatch_concurrent`].
+    ///
+    /// Returns the envelope back if the message can not be handled concurrently.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor which will handle the message.
+    pub fn dispatch_concurrent<A>(
+        self,
+        actor: &A,
+    ) -> Result<impl Future<Output = ()> + Send + '_, Self>
+    where
+        A: Actor + Sync,
+        T: Dispatch<A>,
+    {
+        self.dispatch_concurrent_with_layers(actor, None)
+    }
+
+    /// Dispatches the message to the actor for concurrent handling through the given layers.
+    pub(crate) fn dispatch_concurrent_with_layers<A>(
+        self,
+        actor: &A,
+        layers: Option<Layers<T>>,
+    ) -> Result<impl Future<Output = ()> + Send + '_, Self>
+    where
+        A: Actor + Sync,
+        T: Dispatch<A>,
+    {
+        let span = self.dispatch_span::<A>();
+        let (handled, sender) = self.handle_concurrent(actor, layers)?;
+
+        Ok(span.instrument(async move {
+            if let (Some(ret), Some(sender)) = (handled.await, sender) {
+                sender.send(ret);
             }
+        }))
+    }
+
+    /// Passes the message to the actor for concurrent handling through the given layers,
+    /// returning a future which resolves to the response, if any, and the channel which
+    /// wants it.
+    ///
+    /// Returns the envelope back if the message can not be handled concurrently.
+    #[allow(clippy::type_complexity)]
+    fn handle_concurrent<A>(
+        self,
+        actor: &A,
+        layers: Option<Layers<T>>,
+    ) -> Result<
+        (
+            impl Future<Output = Option<T::Return>> + Send + '_,
+            Option<ResponseSender<T>>,
+        ),
+        Self,
+    >
+    where
+        A: Actor + Sync,
+        T: Dispatch<A>,
+    {
+        let (msg, sender) = match self.inner {
+            EnvelopeInner::NoResponse(msg) => (msg, None),
+            EnvelopeInner::WantsResponse(msg, sender) => (msg, Some(sender)),
+        };
+
+        match layer::dispatch_concurrent(msg, actor, layers) {
+            Ok(handled) => Ok((handled, sender)),
+            Err(msg) => Err(Self {
+                inner: match sender {
+                    Some(sender) => EnvelopeInner::WantsResponse(msg, sender),
+                    None => EnvelopeInner::NoResponse(msg),
+                },
+                ..self
+            }),
         }
     }
+
+    /// Dispatches the message to the actor for handling, catching any panic which occurs
+    /// while it is handled.
+    ///
+    /// If the handler panics, the sender receives [`Error::Panicked`] and the panic message
+    /// is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor which will handle the message.
+    /// * `ctx` - The context of the actor.
+    pub async fn dispatch_catch_unwind<A>(
+        self,
+        actor: &mut A,
+        ctx: &mut Context<A>,
+    ) -> Result<(), String>
+    where
+        A: Actor,
+        T: Dispatch<A>,
+    {
+        let span = self.dispatch_span::<A>();
+        span.instrument(async move {
+            match self.inner {
+                EnvelopeInner::NoResponse(msg) => {
+                    AssertUnwindSafe(layer::dispatch(msg, actor, ctx, move |_| {}))
+                        .catch_unwind()
+                        .await
+                        .map_err(panic_message)
+                }
+                EnvelopeInner::WantsResponse(msg, sender) => {
+                    // The sender is kept outside of the handler so the caller can be notified
+                    // if it panics.
+                    let sender = SharedSender::new(sender);
+                    ctx.set_cancel(Some(sender.cancel()));
+                    let res =
+                        AssertUnwindSafe(layer::dispatch(msg, actor, ctx, |ret| sender.send(ret)))
+                            .catch_unwind()
+                            .await
+                            .map_err(panic_message);
+                    ctx.set_cancel(None);
+
+                    if let Err(message) = &res {
+                        sender.send_err(Error::Panicked {
+                            message: message.clone(),
+                        });
+                    }
+
+                    res
+                }
+            }
+        })
+        .await
+    }
+
+    /// Dispatches the message to the actor for concurrent handling, catching any panic which
+    /// occurs while it is handled.
+    ///
+    /// Returns the envelope back if the message can not be handled concurrently. See
+    /// [`Envelope::dispatch_catch_unwind`].
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor which will handle the message.
+    pub fn dispatch_concurrent_catch_unwind<A>(
+        self,
+        actor: &A,
+    ) -> Result<impl Future<Output = Result<(), String>> + Send + '_, Self>
+    where
+        A: Actor + Sync,
+        T: Dispatch<A>,
+    {
+        self.dispatch_concurrent_catch_unwind_with_layers(actor, None)
+    }
+
+    /// Dispatches the message to the actor for concurrent handling through the given layers,
+    /// catching any panic which occurs while it is handled.
+    pub(crate) fn dispatch_concurrent_catch_unwind_with_layers<A>(
+        self,
+        actor: &A,
+        layers: Option<Layers<T>>,
+    ) -> Result<impl Future<Output = Result<(), String>> + Send + '_, Self>
+    where
+        A: Actor + Sync,
+        T: Dispatch<A>,
+    {
+        let span = self.dispatch_span::<A>();
+        let (handled, sender) = self.handle_concurrent(actor, layers)?;
+
+        Ok(span.instrument(async move {
+            let res = AssertUnwindSafe(handled)
+                .catch_unwind()
+                .await
+                .map_err(panic_message);
+
+            match (res, sender) {
+                (Ok(Some(ret)), Some(sender)) => {
+                    sender.send(ret);
+                    Ok(())
+                }
+                (Err(message), Some(sender)) => {
+                    sender.send_err(Error::Panicked {
+                        message: message.clone(),
+                    });
+                    Err(message)
+                }
+                (res, _) => res.map(|_| ()),
+            }
+        }))
+    }
+
+    /// Dispatches a batch of messages and their return channels to the actor for handling,
+    /// see [`Dispatch::dispatch_batch`].
+    ///
+    /// Every message in the batch must have the same [`Envelope::batch_key`].
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The envelopes to dispatch.
+    /// * `actor` - The actor which will handle the messages.
+    /// * `ctx` - The context of the actor.
+    pub async fn dispatch_batch<A>(batch: Vec<Self>, actor: &mut A, ctx: &mut Context<A>)
+    where
+        A: Actor,
+        T: Dispatch<A>,
+    {
+        let Some(span) = batch.first().map(|env| env.dispatch_span::<A>()) else {
+            return;
+        };
+
+        span.instrument(async move {
+            let batch = Self::into_batch(batch);
+            ctx.set_cancel(Self::batch_cancel(&batch));
+            let batch = batch
+                .into_iter()
+                .map(|(msg, sender)| (msg, respond(sender)))
+                .collect();
+            layer::dispatch_batch(batch, actor, ctx).await;
+            ctx.set_cancel(None);
+        })
+        .await
+    }
+
+    /// Dispatches a batch of messages to the actor for handling, catching any panic which
+    /// occurs while they are handled.
+    ///
+    /// If the handler panics, every sender which has not received a response receives
+    /// [`Error::Panicked`] and the panic message is returned. See
+    /// [`Envelope::dispatch_batch`].
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The envelopes to dispatch.
+    /// * `actor` - The actor which will handle the messages.
+    /// * `ctx` - The context of the actor.
+    pub async fn dispatch_batch_catch_unwind<A>(
+        batch: Vec<Self>,
+        actor: &mut A,
+        ctx: &mut Context<A>,
+    ) -> Result<(), String>
+    where
+        A: Actor,
+        T: Dispatch<A>,
+    {
+        let Some(span) = batch.first().map(|env| env.dispatch_span::<A>()) else {
+            return Ok(());
+        };
+
+        span.instrument(async move {
+            let batch = Self::into_batch(batch);
+            // The senders are kept outside of the handler so the callers can be notified if
+            // it panics.
+            let senders = batch
+                .iter()
+                .filter_map(|(_, sender)| sender.clone())
+                .collect::<Vec<_>>();
+            ctx.set_cancel(Self::batch_cancel(&batch));
+            let batch = batch
+                .into_iter()
+                .map(|(msg, sender)| (msg, respond(sender)))
+                .collect();
+            let res = AssertUnwindSafe(layer::dispatch_batch(batch, actor, ctx))
+                .catch_unwind()
+                .await
+                .map_err(panic_message);
+            ctx.set_cancel(None);
+
+            if let Err(message) = &res {
+                for sender in senders {
+                    sender.send_err(Error::Panicked {
+                        message: message.clone(),
+                    });
+                }
+            }
+
+            res
+        })
+        .await
+    }
+}
+
+/// The span of a dispatched message, see the `tracing` feature.
+#[cfg(feature = "tracing")]
+struct DispatchSpan(tracing::Span);
+
+/// The span of a dispatched message, see the `tracing` feature.
+#[cfg(not(feature = "tracing"))]
+struct DispatchSpan;
+
+impl DispatchSpan {
+    /// Instruments a dispatch future with the span.
+    fn instrument<F: Future>(self, fut: F) -> impl Future<Output = F::Output> {
+        #[cfg(feature = "tracing")]
+        return tracing::Instrument::instrument(fut, self.0);
+
+        #[cfg(not(feature = "tracing"))]
+        fut
+    }
+}
+
+/// Returns a channel which sends a response to the sender, if there is one.
+fn respond<T: Message>(sender: Option<SharedSender<T>>) -> impl FnOnce(T::Return) + Send {
+    move |ret| {
+        if let Some(sender) = sender {
+            sender.send(ret);
+        }
+    }
+}
+
+/// Returns the message of a panic payload.
+pub(crate) fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "handler panicked".to_string()
+    }
 }
@@ -2,6 +2,7 @@ use std::fmt::Display;
 
 /// Errors that can occur when sending a message.
 #[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum Error {
     /// The mailbox has been disconnected.
@@ -10,6 +11,15 @@ pub enum Error {
     Interrupted,
     /// Error occurred while wrapping a message.
     Wrapper,
+    /// The actor stopped before handling the message.
+    Stopped,
+    /// The message was not handled in time.
+    Timeout,
+    /// The actor panicked while handling the message.
+    Panicked {
+        /// The panic message.
+        message: String,
+    },
 }
 
 impl Display for Error {
@@ -18,8 +28,65 @@ impl Display for Error {
             Error::Disconnected => write!(f, "mailbox disconnected"),
             Error::Interrupted => write!(f, "message handling interrupted"),
             Error::Wrapper => write!(f, "wrapper error"),
+            Error::Stopped => write!(f, "actor stopped"),
+            Error::Timeout => write!(f, "message handling timed out"),
+            Error::Panicked { message } => write!(f, "actor panicked: {}", message),
         }
     }
 }
 
 impl std::error::Error for Error {}
+
+/// Error returned when a message could not be queued without waiting.
+///
+/// The message is handed back to the caller, see [`TrySendError::into_inner`].
+#[derive(PartialEq, Eq, Hash)]
+pub enum TrySendError<T> {
+    /// The mailbox is full.
+    Full(T),
+    /// The mailbox has been disconnected.
+    Disconnected(T),
+    /// The actor has stopped receiving messages.
+    Stopped(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Returns `true` if the mailbox is full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full(_))
+    }
+
+    /// Returns `true` if the mailbox is no longer receiving messages.
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self, Self::Disconnected(_) | Self::Stopped(_))
+    }
+
+    /// Returns the message which could not be queued.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(msg) | Self::Disconnected(msg) | Self::Stopped(msg) => msg,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "Full(..)"),
+            Self::Disconnected(_) => write!(f, "Disconnected(..)"),
+            Self::Stopped(_) => write!(f, "Stopped(..)"),
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "mailbox full"),
+            Self::Disconnected(_) => write!(f, "mailbox disconnected"),
+            Self::Stopped(_) => write!(f, "actor stopped"),
+        }
+    }
+}
+
+impl<T> std::error::Error for TrySendError<T> {}
@@ -3,15 +3,18 @@
 use std::{
     marker::PhantomData,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use futures_core::{ready, FusedFuture, Future};
 use futures_util::FutureExt;
 
 use crate::{
-    channel::{new_response, ChannelError, OneshotReceiver, ResponseSender, Sender},
-    Envelope, Error, Message,
+    channel::{new_response, Cancel, ChannelError, Response, ResponseSender},
+    timer::{default_timer, Sleep, Timer},
+    Address, Envelope, Error, Message,
 };
 
 /// A [`MessageFuture`] mode which will wait for a response.
@@ -43,6 +46,17 @@ impl<T: Message> MessageFuture<T, Wait> {
         }
     }
 
+    /// Returns a future which fails with [`Error::Timeout`] if a response is not received
+    /// within `duration`.
+    ///
+    /// The timeout uses the timer of the [`Runner`](crate::Runner) which receives from the
+    /// mailbox, or the [`default_timer`] if it does not have one. If neither is available,
+    /// the response is waited for without a timeout.
+    pub fn timeout(self, duration: Duration) -> Timeout<Self> {
+        let timer = self.queue.addr.timer();
+        Timeout::with_timer(self, timer, duration)
+    }
+
     /// Returns a new [`MessageFuture`] which will instead resolve when the message is sent and
     /// return a [`ResponseFuture`] which can be used to wait for the response.
     pub fn detach(self) -> MessageFuture<T, Detach> {
@@ -102,14 +116,14 @@ impl<T: Message> FusedFuture for MessageFuture<T, Wait> {
 /// A future which resolves when a message is successfully queued.
 #[must_use = "futures do nothing unless polled"]
 pub struct QueueFuture<T: Message> {
-    sender: Sender<T>,
+    addr: Address<T>,
     msg: Option<Envelope<T>>,
 }
 
 impl<T: Message> QueueFuture<T> {
-    pub(crate) fn new(sender: Sender<T>, msg: Envelope<T>) -> Self {
+    pub(crate) fn new(addr: Address<T>, msg: Envelope<T>) -> Self {
         Self {
-            sender,
+            addr,
             msg: Some(msg),
         }
     }
@@ -120,11 +134,16 @@ impl<T: Message> Future for QueueFuture<T> {
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
-        if this.msg.is_some() {
-            ready!(this.sender.poll_ready(cx).map_err(|_| Error::Disconnected))?;
-            if let Err(err) = this.sender.try_send(this.msg.take().unwrap()) {
+        if let Some(priority) = this.msg.as_ref().map(|msg| msg.priority()) {
+            ready!(this
+                .addr
+                .poll_ready(cx, priority)
+                .map_err(|_| this.addr.send_error()))?;
+            if let Err(err) = this.addr.try_send_envelope(this.msg.take().unwrap()) {
                 match err {
-                    ChannelError::Disconnected => return Poll::Ready(Err(Error::Disconnected)),
+                    ChannelError::Disconnected(_) => {
+                        return Poll::Ready(Err(this.addr.send_error()))
+                    }
                     ChannelError::Full(msg) => {
                         this.msg = Some(msg);
                         return Poll::Pending;
@@ -145,27 +164,138 @@ impl<T: Message> FusedFuture for QueueFuture<T> {
 
 /// A future which returns the response to a message.
 #[must_use = "futures do nothing unless polled"]
-pub struct ResponseFuture<T: Message>(pub(crate) OneshotReceiver<T::Return>);
+pub struct ResponseFuture<T: Message>(pub(crate) Option<Arc<Response<T>>>);
 
 impl<T: Message> ResponseFuture<T> {
     /// Returns a new [`ResponseSender`] and [`ResponseFuture`].
     pub fn new() -> (ResponseSender<T>, Self) {
         new_response()
     }
+
+    /// Returns a future which fails with [`Error::Timeout`] if a response is not received
+    /// within `duration`, using the [`default_timer`].
+    ///
+    /// If no default timer is available, the response is waited for without a timeout, see
+    /// [`set_default_timer`](crate::timer::set_default_timer).
+    pub fn timeout(self, duration: Duration) -> Timeout<Self> {
+        Timeout::with_timer(self, default_timer(), duration)
+    }
+}
+
+impl<T: Message> std::fmt::Debug for ResponseFuture<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseFuture").finish_non_exhaustive()
+    }
 }
 
 impl<T: Message> Future for ResponseFuture<T> {
     type Output = Result<T::Return, Error>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Pin::new(&mut self.get_mut().0)
-            .poll(cx)
-            .map_err(|_| Error::Interrupted)
+        let this = self.get_mut();
+        let Some(response) = &this.0 else {
+            // The response has already been received.
+            return Poll::Ready(Err(Error::Interrupted));
+        };
+
+        let res = ready!(response.poll_response(cx));
+        this.0 = None;
+        Poll::Ready(res)
     }
 }
 
 impl<T: Message> FusedFuture for ResponseFuture<T> {
     fn is_terminated(&self) -> bool {
-        self.0.is_terminated()
+        self.0.is_none()
+    }
+}
+
+impl<T: Message> Drop for ResponseFuture<T> {
+    fn drop(&mut self) {
+        if let Some(response) = self.0.take() {
+            response.cancel();
+        }
+    }
+}
+
+/// A future which fails with [`Error::Timeout`] if the inner future does not complete in time.
+#[must_use = "futures do nothing unless polled"]
+pub struct Timeout<F> {
+    fut: F,
+    /// `None` if no timer is available, in which case the timeout never expires.
+    sleep: Option<Sleep>,
+}
+
+impl<F> Timeout<F> {
+    /// Creates a new timeout which expires once `sleep` completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `fut` - The future to run.
+    /// * `sleep` - A future which completes when the timeout expires, see
+    ///   [`Timer::sleep`].
+    pub fn new(fut: F, sleep: Sleep) -> Self {
+        Self {
+            fut,
+            sleep: Some(sleep),
+        }
+    }
+
+    fn with_timer(fut: F, timer: Option<Arc<dyn Timer>>, duration: Duration) -> Self {
+        Self {
+            fut,
+            sleep: timer.map(|timer| timer.sleep(duration)),
+        }
+    }
+
+    /// Returns the inner future.
+    pub fn into_inner(self) -> F {
+        self.fut
+    }
+}
+
+impl<F, T> Future for Timeout<F>
+where
+    F: Future<Output = Result<T, Error>> + Unpin,
+{
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Poll::Ready(res) = this.fut.poll_unpin(cx) {
+            return Poll::Ready(res);
+        }
+
+        match &mut this.sleep {
+            Some(sleep) => {
+                ready!(sleep.poll_unpin(cx));
+                Poll::Ready(Err(Error::Timeout))
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// A future which completes once the sender of a message is no longer waiting for a response.
+///
+/// See [`Context::cancellation`](crate::Context::cancellation).
+#[must_use = "futures do nothing unless polled"]
+pub struct Cancellation(Option<Arc<dyn Cancel>>);
+
+impl Cancellation {
+    pub(crate) fn new(cancel: Option<Arc<dyn Cancel>>) -> Self {
+        Self(cancel)
+    }
+}
+
+impl Future for Cancellation {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &self.0 {
+            Some(cancel) => cancel.poll_canceled(cx),
+            None => Poll::Pending,
+        }
     }
 }
@@ -0,0 +1,276 @@
+use std::{future::Future, sync::Arc};
+
+use futures_channel::oneshot;
+use futures_core::future::BoxFuture;
+use futures_util::{
+    future::{join, join_all},
+    FutureExt,
+};
+
+use crate::{Actor, Context, Dispatch, Message};
+
+/// Middleware which wraps the dispatch of every message an actor handles.
+///
+/// Layers are registered with [`Runner::with_layer`](crate::Runner::with_layer), and apply to
+/// every message received from the actor's mailbox, including messages which are handled
+/// concurrently. A layer can inspect the message before it is handled, short-circuit by
+/// returning a response without calling [`Next::run`], or post-process the return value.
+///
+/// Returning `None` drops the message without a response, in which case the sender receives
+/// [`Error::Interrupted`](crate::Error::Interrupted).
+pub trait Layer<T: Message>: Send + Sync + 'static {
+    /// Dispatches a message, eg. by passing it on to the next layer with [`Next::run`].
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to dispatch.
+    /// * `next` - The remaining layers, and ultimately the actor's handler.
+    fn dispatch<'a>(&'a self, msg: T, next: Next<'a, T>) -> BoxFuture<'a, Option<T::Return>>;
+}
+
+/// The layers of an actor, ordered from outermost to innermost.
+pub(crate) type Layers<T> = Arc<[Arc<dyn Layer<T>>]>;
+
+/// The layers of a [`Runner`](crate::Runner) which handle messages of type `T`.
+///
+/// Created with [`Runner::with_layer`](crate::Runner::with_layer). A runner with layers can
+/// only run an actor with a mailbox of messages of type `T`.
+pub struct LayerStack<T: Message>(Vec<Arc<dyn Layer<T>>>);
+
+impl<T: Message> LayerStack<T> {
+    pub(crate) fn new(layer: impl Layer<T>) -> Self {
+        Self(vec![Arc::new(layer)])
+    }
+
+    /// Adds an innermost layer.
+    pub(crate) fn push(&mut self, layer: impl Layer<T>) {
+        self.0.push(Arc::new(layer));
+    }
+}
+
+impl<T: Message> Clone for LayerStack<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Message> std::fmt::Debug for LayerStack<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LayerStack")
+            .field("layers", &self.0.len())
+            .finish()
+    }
+}
+
+/// The layers which a [`Runner`](crate::Runner) wraps the dispatch of messages of type `T`
+/// with.
+///
+/// Implemented by `()` for a runner without layers, which can run an actor with a mailbox of
+/// any type of message, and by [`LayerStack<T>`].
+pub trait RunnerLayers<T: Message>: Clone + Send + Sync + 'static {
+    /// Returns the layers ordered from outermost to innermost, or `None` if there are none.
+    fn layers(&self) -> Option<Arc<[Arc<dyn Layer<T>>]>>;
+}
+
+impl<T: Message> RunnerLayers<T> for () {
+    fn layers(&self) -> Option<Arc<[Arc<dyn Layer<T>>]>> {
+        None
+    }
+}
+
+impl<T: Message> RunnerLayers<T> for LayerStack<T> {
+    fn layers(&self) -> Option<Arc<[Arc<dyn Layer<T>>]>> {
+        Some(self.0.iter().cloned().collect())
+    }
+}
+
+type Handle<'a, T> =
+    Box<dyn FnOnce(T) -> BoxFuture<'a, Option<<T as Message>::Return>> + Send + 'a>;
+
+/// The remainder of a chain of [`Layer`]s, ending with the actor's handler.
+pub struct Next<'a, T: Message> {
+    layers: &'a [Arc<dyn Layer<T>>],
+    handle: Handle<'a, T>,
+}
+
+impl<T: Message> std::fmt::Debug for Next<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("layers", &self.layers.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a, T: Message> Next<'a, T> {
+    /// Passes the message on to the next layer, or to the actor's handler if this is the last
+    /// layer, returning the response.
+    pub fn run(self, msg: T) -> BoxFuture<'a, Option<T::Return>> {
+        match self.layers.split_first() {
+            Some((layer, layers)) => layer.dispatch(
+                msg,
+                Next {
+                    layers,
+                    handle: self.handle,
+                },
+            ),
+            None => (self.handle)(msg),
+        }
+    }
+}
+
+/// Dispatches a message to the actor through its layers.
+pub(crate) async fn dispatch<A, T, R>(msg: T, actor: &mut A, ctx: &mut Context<A>, ret: R)
+where
+    A: Actor,
+    T: Dispatch<A>,
+    R: FnOnce(T::Return) + Send,
+{
+    let Some(layers) = ctx.layers::<T>() else {
+        return msg.dispatch(actor, ctx, ret).await;
+    };
+
+    run(
+        msg,
+        &layers,
+        move |msg, tx| msg.dispatch(actor, ctx, move |value| _ = tx.send(value)),
+        ret,
+    )
+    .await
+}
+
+/// Dispatches a message to the actor for concurrent handling through its layers, returning
+/// a future which resolves to the response, if any.
+///
+/// Returns the message back if it can not be handled concurrently.
+pub(crate) fn dispatch_concurrent<A, T>(
+    msg: T,
+    actor: &A,
+    layers: Option<Layers<T>>,
+) -> Result<impl Future<Output = Option<T::Return>> + Send + '_, T>
+where
+    A: Actor + Sync,
+    T: Dispatch<A>,
+{
+    let Some(layers) = layers else {
+        return msg
+            .dispatch_concurrent(actor)
+            .map(|handled| handled.map(Some).left_future());
+    };
+    if !msg.is_concurrent() {
+        return Err(msg);
+    }
+
+    Ok(async move {
+        let mut response = None;
+        run(
+            msg,
+            &layers,
+            move |msg, tx| async move {
+                // A message which a layer passes on in its place is dropped if it can not be
+                // handled concurrently.
+                if let Ok(handled) = msg.dispatch_concurrent(actor) {
+                    _ = tx.send(handled.await);
+                }
+            },
+            |value| response = Some(value),
+        )
+        .await;
+        response
+    }
+    .right_future())
+}
+
+/// Dispatches a batch of messages to the actor through its layers.
+///
+/// Every message is passed through the layers on its own, and the messages which reach the
+/// end of the chain are handled together as one batch.
+pub(crate) async fn dispatch_batch<A, T, R>(batch: Vec<(T, R)>, actor: &mut A, ctx: &mut Context<A>)
+where
+    A: Actor,
+    T: Dispatch<A>,
+    R: FnOnce(T::Return) + Send,
+{
+    let Some(layers) = ctx.layers::<T>() else {
+        return T::dispatch_batch(batch, actor, ctx).await;
+    };
+
+    let (chains, handled): (Vec<_>, Vec<_>) = batch
+        .into_iter()
+        .map(|(msg, ret)| {
+            let (msg_tx, msg_rx) = oneshot::channel();
+            let (ret_tx, ret_rx) = oneshot::channel();
+            (chain(msg, &layers, msg_tx, ret_rx, ret), (msg_rx, ret_tx))
+        })
+        .unzip();
+
+    // The batch is handled once every message has either been passed on by the last layer,
+    // or dropped by a layer.
+    let handler = async move {
+        let batch = join_all(handled.into_iter().map(|(msg_rx, ret_tx)| async move {
+            let msg = msg_rx.await.ok()?;
+            Some((msg, move |value| _ = ret_tx.send(value)))
+        }))
+        .await
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+
+        if !batch.is_empty() {
+            T::dispatch_batch(batch, actor, ctx).await;
+        }
+    };
+
+    join(join_all(chains), handler).await;
+}
+
+/// Passes a message through a chain of layers to a handler, returning the response.
+///
+/// The handler is driven next to the layers rather than by them, so a response which is
+/// returned before the handler finishes, eg. a [`ResponseStream`](crate::ResponseStream),
+/// passes through the layers to the sender while the handler keeps running.
+async fn run<T, H, F, R>(msg: T, layers: &[Arc<dyn Layer<T>>], handle: H, ret: R)
+where
+    T: Message,
+    H: FnOnce(T, oneshot::Sender<T::Return>) -> F,
+    F: Future<Output = ()>,
+    R: FnOnce(T::Return) + Send,
+{
+    let (msg_tx, msg_rx) = oneshot::channel();
+    let (ret_tx, ret_rx) = oneshot::channel();
+
+    // The handler only runs if the last layer passes the message on.
+    let handler = async move {
+        if let Ok(msg) = msg_rx.await {
+            handle(msg, ret_tx).await;
+        }
+    };
+
+    join(chain(msg, layers, msg_tx, ret_rx, ret), handler).await;
+}
+
+/// Passes a message through a chain of layers, returning the response.
+///
+/// The last layer passes the message on to the handler through `msg_tx`, and waits for its
+/// response on `ret_rx`.
+async fn chain<T, R>(
+    msg: T,
+    layers: &[Arc<dyn Layer<T>>],
+    msg_tx: oneshot::Sender<T>,
+    ret_rx: oneshot::Receiver<T::Return>,
+    ret: R,
+) where
+    T: Message,
+    R: FnOnce(T::Return) + Send,
+{
+    let next = Next {
+        layers,
+        handle: Box::new(move |msg: T| {
+            _ = msg_tx.send(msg);
+            async move { ret_rx.await.ok() }.boxed()
+        }),
+    };
+
+    if let Some(value) = next.run(msg).await {
+        ret(value);
+    }
+}
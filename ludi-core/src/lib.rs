@@ -7,26 +7,88 @@
 #![deny(clippy::all)]
 
 mod address;
+mod attach;
+mod broker;
 mod channel;
 mod envelope;
 mod error;
 pub mod futures;
+mod layer;
 mod mailbox;
+pub mod metrics;
+pub mod persistence;
+mod priority;
+mod recipient;
+mod registry;
+mod runner;
+mod select;
+pub mod spawner;
+mod stream;
+mod supervisor;
+pub mod timer;
 
-use futures_core::Stream;
-use futures_util::StreamExt;
-use std::future::Future;
+use futures_core::{future::BoxFuture, ready, stream::BoxStream, Stream};
+use futures_util::{
+    future::{poll_fn, AbortHandle, Abortable, Either},
+    stream::{FuturesUnordered, SelectAll},
+    FutureExt, StreamExt,
+};
+use std::{
+    any::{Any, TypeId},
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::Arc,
+    task::Poll,
+    time::Duration,
+};
 
-pub use address::Address;
+pub use address::{Address, WeakAddress};
+pub use attach::StreamHandler;
+pub use broker::{Broker, Subscriber, SubscriptionId, Topic};
 pub use channel::ResponseSender;
 pub use envelope::Envelope;
-pub use error::Error;
-pub use mailbox::{mailbox, unbounded_mailbox, IntoMail, IntoMailbox, Mailbox};
+pub use error::{Error, TrySendError};
+pub use layer::{Layer, LayerStack, Next, RunnerLayers};
+pub use mailbox::{
+    mailbox, priority_mailbox, priority_mailbox_with_policy, unbounded_mailbox, IntoMail,
+    IntoMailbox, Mailbox,
+};
+pub use priority::{Priority, PriorityPolicy};
+pub use recipient::Recipient;
+pub use registry::{Registry, Watch};
+pub use runner::Runner;
+pub use select::{MailboxSet, SelectPolicy};
+pub use spawner::{ActorHandle, JoinError};
+pub use stream::{ResponseStream, StreamMessage, StreamSender, StreamingHandler};
+pub use supervisor::{RestartIntensity, RestartIntensityExceeded, Strategy, Supervisor};
+
+use attach::StreamEvent;
+use channel::Cancel;
+use futures::Cancellation;
+use layer::Layers;
+use mailbox::{DeferInbox, Inbox, StreamInbox};
+use metrics::{MetricsSink, Probe};
+use timer::{Timer, TimerHandle};
 
 /// A message type.
 pub trait Message: Send + Unpin + 'static {
     /// The return value of the message.
     type Return: Send + Unpin + 'static;
+
+    /// Returns the priority of the message, see [`priority_mailbox`].
+    ///
+    /// By default this returns [`Priority::NORMAL`].
+    fn priority(&self) -> Priority {
+        Priority::NORMAL
+    }
+
+    /// Returns a label which identifies the message in [`metrics`].
+    ///
+    /// By default this returns the name of the message type. Wrapper messages return the
+    /// name of the variant.
+    fn label(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
 }
 
 /// A message which can wrap another type of message.
@@ -56,6 +118,71 @@ pub trait Dispatch<A: Actor>: Message {
         ctx: &mut Context<A>,
         ret: R,
     ) -> impl Future<Output = ()> + Send;
+
+    /// Returns `true` if the message can be handled concurrently with other such messages
+    /// using [`Dispatch::dispatch_concurrent`].
+    ///
+    /// Only used by a runner with layers, which must decide how to handle a message before
+    /// passing it through them. By default this returns `false`. See [`ConcurrentHandler`].
+    fn is_concurrent(&self) -> bool {
+        false
+    }
+
+    /// Dispatches the message to the actor for handling using a shared reference, returning a
+    /// future which resolves to the value to return to the caller.
+    ///
+    /// Returns the message back if it can not be handled concurrently, in which case it is
+    /// dispatched with [`Dispatch::dispatch`] once the actor is no longer shared. By default
+    /// every message is returned back.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor which will handle the message.
+    fn dispatch_concurrent(
+        self,
+        actor: &A,
+    ) -> Result<impl Future<Output = Self::Return> + Send + '_, Self>
+    where
+        Self: Sized,
+        A: Sync,
+    {
+        let _ = actor;
+        Err::<std::future::Pending<Self::Return>, _>(self)
+    }
+
+    /// Returns a key which identifies the messages this message can be handled in a batch
+    /// with using [`Dispatch::dispatch_batch`].
+    ///
+    /// By default this returns `None`. See [`BatchHandler`].
+    fn batch_key(&self) -> Option<TypeId> {
+        None
+    }
+
+    /// Dispatches a batch of messages and their return channels to the actor for handling.
+    ///
+    /// This method is only called with messages which return the same
+    /// [`Dispatch::batch_key`]. By default every message is dispatched one at a time with
+    /// [`Dispatch::dispatch`].
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The messages and their return channels.
+    /// * `actor` - The actor which will handle the messages.
+    /// * `ctx` - The context of the actor.
+    fn dispatch_batch<R: FnOnce(Self::Return) + Send>(
+        batch: Vec<(Self, R)>,
+        actor: &mut A,
+        ctx: &mut Context<A>,
+    ) -> impl Future<Output = ()> + Send
+    where
+        Self: Sized,
+    {
+        async move {
+            for (msg, ret) in batch {
+                msg.dispatch(actor, ctx, ret).await;
+            }
+        }
+    }
 }
 
 /// An actor.
@@ -69,6 +196,11 @@ pub trait Dispatch<A: Actor>: Message {
 ///
 /// When an actor receives a stop signal it will stop processing messages and the [`Actor::stopped`] method
 /// will be called before returning.
+///
+/// # Panic
+///
+/// When run with a panic boundary, a panic in a handler is reported to the sender of the message
+/// as [`Error::Panicked`] and the [`Actor::panicked`] method is called.
 pub trait Actor: Send + Sized {
     /// The type of value returned when this actor is stopped.
     type Stop;
@@ -83,6 +215,21 @@ pub trait Actor: Send + Sized {
 
     /// A method which is called when the actor receives a stop signal.
     fn stopped(&mut self) -> impl Future<Output = Result<Self::Stop, Self::Error>> + Send;
+
+    /// A method which is called when a handler panics, if the actor is run with a panic
+    /// boundary, see [`Runner::with_catch_panics`].
+    ///
+    /// By default the actor is stopped. Override this method to keep the actor running, or
+    /// to propagate an error with [`Context::set_error`].
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The panic message.
+    /// * `ctx` - The actor's execution context.
+    fn panicked(&mut self, message: &str, ctx: &mut Context<Self>) {
+        let _ = message;
+        ctx.stop();
+    }
 }
 
 /// An actor that can handle a message.
@@ -133,25 +280,200 @@ pub trait Handler<T: Message>: Actor {
     }
 }
 
+/// An actor that can handle a message using a shared reference.
+///
+/// Messages handled this way can not mutate the actor, which allows them to be handled
+/// concurrently with each other, see [`run_concurrent`]. Messages which require exclusive
+/// access are still handled one at a time.
+///
+/// A message opts in to concurrent handling via its [`Dispatch`] implementation, eg. with
+/// `#[ludi(concurrent)]` or `#[msg(concurrent)]` when using the macros.
+pub trait ConcurrentHandler<T: Message>: Handler<T> + Sync {
+    /// Handle a message and return a value to the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to handle.
+    fn handle_concurrent(&self, msg: T) -> impl Future<Output = T::Return> + Send;
+}
+
+/// An actor that can handle a batch of messages at once.
+///
+/// This is useful for actors which perform I/O for each message, such as writing to a
+/// database, which can be done once for many messages instead.
+///
+/// When a message which is handled in batches is received, every message of the same type
+/// which is queued directly behind it is taken without waiting, up to the maximum batch size,
+/// see [`Runner::with_max_batch_size`]. Messages are never reordered, so a batch ends at the
+/// first queued message of another type.
+///
+/// A message opts in to batch handling via its [`Dispatch`] implementation, eg. with
+/// `#[ludi(batch)]` when using the macros. Each message of a batch is passed through the
+/// [`Layer`]s on its own, and the messages which reach the handler are handled as one batch.
+/// [`Context::is_canceled`] returns `true` once every sender of the batch has canceled.
+pub trait BatchHandler<T: Message>: Actor {
+    /// Handle a batch of messages and return a value to the caller of each.
+    ///
+    /// The values are returned to the callers in the order of the messages. A caller whose
+    /// message has no corresponding value receives [`Error::Interrupted`].
+    ///
+    /// # Arguments
+    ///
+    /// * `msgs` - The messages to handle, in the order they were received.
+    /// * `ctx` - The actor's execution context.
+    fn handle_batch(
+        &mut self,
+        msgs: Vec<T>,
+        ctx: &mut Context<Self>,
+    ) -> impl Future<Output = Vec<T::Return>> + Send;
+
+    /// Handle a batch of messages and return a value to the caller of each. This method is
+    /// similar to [`BatchHandler::handle_batch`] except that it gives more control over how
+    /// the messages are handled, see [`Handler::process`].
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The messages to handle and their return channels.
+    /// * `ctx` - The actor's execution context.
+    fn process_batch<R: FnOnce(T::Return) + Send>(
+        &mut self,
+        batch: Vec<(T, R)>,
+        ctx: &mut Context<Self>,
+    ) -> impl Future<Output = ()> + Send {
+        async move {
+            let (msgs, rets): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+            let values = self.handle_batch(msgs, ctx).await;
+            for (ret, value) in rets.into_iter().zip(values) {
+                ret(value);
+            }
+        }
+    }
+}
+
+/// Determines what happens to queued messages when an actor is stopped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum StopMode {
+    /// Stop immediately, leaving any queued messages in the mailbox.
+    ///
+    /// The senders of these messages receive [`Error::Stopped`] once the mailbox is dropped.
+    #[default]
+    Drop,
+    /// Close the mailbox and handle every message which is already queued before stopping.
+    ///
+    /// Any attempt to send a new message fails with [`Error::Stopped`].
+    Drain,
+    /// Close the mailbox and reject every message which is already queued with
+    /// [`Error::Stopped`].
+    ///
+    /// Any attempt to send a new message fails with [`Error::Stopped`].
+    Reject,
+}
+
+/// The maximum number of messages handled in a batch, unless configured otherwise.
+const DEFAULT_MAX_BATCH_SIZE: usize = 64;
+
 /// An actor's execution context.
 pub struct Context<A: Actor> {
     stopped: bool,
+    stop_mode: StopMode,
+    catch_panics: bool,
+    skip_canceled: bool,
+    max_batch_size: usize,
+    /// Cancellation signal of the message which is being handled.
+    cancel: Option<Arc<dyn Cancel>>,
     err: Option<A::Error>,
+    /// Weak addresses to the actor's own mailboxes.
+    addrs: Vec<Box<dyn Any + Send>>,
+    /// The layers which wrap the dispatch of messages from the actor's mailbox.
+    layers: Option<Box<dyn Any + Send>>,
+    timer: Option<Arc<dyn Timer>>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    scheduled: FuturesUnordered<BoxFuture<'static, ()>>,
+    /// Streams which are attached to the actor, see [`Context::add_stream`].
+    streams: SelectAll<BoxStream<'static, Box<dyn StreamEvent<A>>>>,
+    /// Whether the attached streams are polled before the mailbox, alternated for fairness.
+    streams_first: bool,
 }
 
 impl<A: Actor> Default for Context<A> {
     fn default() -> Self {
         Self {
             stopped: Default::default(),
+            stop_mode: Default::default(),
+            catch_panics: Default::default(),
+            skip_canceled: Default::default(),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            cancel: Default::default(),
             err: Default::default(),
+            addrs: Default::default(),
+            layers: Default::default(),
+            timer: Default::default(),
+            metrics: Default::default(),
+            scheduled: Default::default(),
+            streams: Default::default(),
+            streams_first: Default::default(),
         }
     }
 }
 
 impl<A: Actor> Context<A> {
+    /// Adds a mailbox of the actor, which messages sent to it are timed and measured for, and
+    /// which is returned by [`Context::address`].
+    pub(crate) fn add_mailbox<T: Message>(&mut self, mailbox: &Mailbox<T>) {
+        let addr = mailbox.weak_address();
+        if let Some(timer) = &self.timer {
+            addr.set_timer(timer.clone());
+        }
+        if self.metrics.is_some() {
+            addr.set_metrics();
+        }
+        self.addrs.push(Box::new(addr));
+    }
+
+    pub(crate) fn set_layers<T: Message>(&mut self, layers: Layers<T>) {
+        self.layers = Some(Box::new(layers));
+    }
+
+    pub(crate) fn set_catch_panics(&mut self, catch_panics: bool) {
+        self.catch_panics = catch_panics;
+    }
+
+    pub(crate) fn set_skip_canceled(&mut self, skip_canceled: bool) {
+        self.skip_canceled = skip_canceled;
+    }
+
+    pub(crate) fn set_max_batch_size(&mut self, max_batch_size: usize) {
+        self.max_batch_size = max_batch_size.max(1);
+    }
+
+    pub(crate) fn set_cancel(&mut self, cancel: Option<Arc<dyn Cancel>>) {
+        self.cancel = cancel;
+    }
+
+    pub(crate) fn set_timer(&mut self, timer: Arc<dyn Timer>) {
+        self.timer = Some(timer);
+    }
+
+    pub(crate) fn set_metrics(&mut self, metrics: Arc<dyn MetricsSink>) {
+        self.metrics = Some(metrics);
+    }
+
     /// Signals to the actor that it should stop processing messages.
+    ///
+    /// Any queued messages are left in the mailbox, see [`StopMode::Drop`].
     pub fn stop(&mut self) {
+        self.stop_with(StopMode::Drop);
+    }
+
+    /// Signals to the actor that it should stop processing messages, using the given mode to
+    /// handle any queued messages.
+    ///
+    /// Closing the mailbox is only supported when the actor is run by a [`Runner`]. Otherwise
+    /// [`StopMode::Drain`] only handles messages which are immediately available, and
+    /// [`StopMode::Reject`] only rejects them.
+    pub fn stop_with(&mut self, mode: StopMode) {
         self.stopped = true;
+        self.stop_mode = mode;
     }
 
     /// Returns `true` if the actor has received a stop signal.
@@ -191,6 +513,216 @@ impl<A: Actor> Context<A> {
             }
         }
     }
+
+    /// Returns `true` if the sender of the message which is being handled is no longer waiting
+    /// for a response.
+    ///
+    /// Always returns `false` for messages which do not expect a response.
+    pub fn is_canceled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.is_canceled())
+    }
+
+    /// Returns a future which completes once the sender of the message which is being handled
+    /// is no longer waiting for a response.
+    ///
+    /// The future never completes for messages which do not expect a response, or once a
+    /// response has been sent.
+    pub fn cancellation(&self) -> Cancellation {
+        Cancellation::new(self.cancel.clone())
+    }
+
+    /// Returns an address to the actor's own mailbox.
+    ///
+    /// The context only holds a weak reference to the mailbox, so an actor does not keep its
+    /// own mailbox open. Returns `None` if every other address to the mailbox has been
+    /// dropped, if no mailbox of the actor receives messages of type `T`, or if the actor is
+    /// not being run by a [`Runner`].
+    pub fn address<T: Message>(&self) -> Option<Address<T>> {
+        self.weak_address::<T>().and_then(|addr| addr.upgrade())
+    }
+
+    /// Sends a message to the actor's own mailbox once `delay` has elapsed.
+    ///
+    /// The message must be of the same type as the messages received by the actor's mailbox,
+    /// eg. the actor's wrapper message.
+    ///
+    /// Returns `None` if no timer is available, or if the actor's mailbox is not known to
+    /// receive messages of type `T`. The timer of the actor's [`Runner`] is used, or the
+    /// [`default_timer`](timer::default_timer) if the runner does not have one.
+    pub fn notify_later<T: Message>(&mut self, msg: T, delay: Duration) -> Option<TimerHandle> {
+        let sleep = self.timer()?.sleep(delay);
+        let addr = self.weak_address::<T>()?.clone();
+
+        Some(self.schedule(async move {
+            sleep.await;
+            if let Some(addr) = addr.upgrade() {
+                _ = addr.queue(msg).await;
+            }
+        }))
+    }
+
+    /// Sends a message to the actor's own mailbox every `period`, until the returned handle is
+    /// canceled or the mailbox is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - A closure which produces the message to send.
+    /// * `period` - The interval between messages.
+    ///
+    /// Returns `None` if no timer is available, or if the actor's mailbox is not known to
+    /// receive messages of type `T`, see [`Context::notify_later`].
+    pub fn notify_interval<T: Message>(
+        &mut self,
+        mut f: impl FnMut() -> T + Send + 'static,
+        period: Duration,
+    ) -> Option<TimerHandle> {
+        let timer = self.timer()?;
+        let addr = self.weak_address::<T>()?.clone();
+
+        Some(self.schedule(async move {
+            loop {
+                timer.sleep(period).await;
+
+                let Some(addr) = addr.upgrade() else {
+                    break;
+                };
+
+                if addr.queue(f()).await.is_err() {
+                    break;
+                }
+            }
+        }))
+    }
+
+    /// Attaches a stream to the actor, so each of its items is handled by the actor along
+    /// with the messages from its mailbox.
+    ///
+    /// Items are handled with [`Handler::process`], and [`StreamHandler::finished`] is called
+    /// once the stream ends. The stream and the mailbox are polled in turn, so neither can
+    /// starve the other. The stream is dropped once the actor stops, which it still does once
+    /// its mailbox is closed.
+    pub fn add_stream<S>(&mut self, stream: S)
+    where
+        A: StreamHandler<S::Item> + 'static,
+        S: Stream + Send + 'static,
+        S::Item: Message,
+    {
+        self.streams.push(attach::events(stream));
+    }
+
+    fn timer(&self) -> Option<Arc<dyn Timer>> {
+        self.timer.clone().or_else(timer::default_timer)
+    }
+
+    fn weak_address<T: Message>(&self) -> Option<&WeakAddress<T>> {
+        self.addrs
+            .iter()
+            .find_map(|addr| addr.downcast_ref::<WeakAddress<T>>())
+    }
+
+    /// Returns the layers which wrap the dispatch of messages of type `T`.
+    pub(crate) fn layers<T: Message>(&self) -> Option<Layers<T>> {
+        self.layers
+            .as_ref()
+            .and_then(|layers| layers.downcast_ref::<Layers<T>>())
+            .cloned()
+    }
+
+    /// Starts measuring the handling of a message, if metrics are enabled.
+    fn probe<T: Message>(&self, env: &Envelope<T>) -> Option<Probe> {
+        let sink = self.metrics.clone()?;
+        let queue_depth = self
+            .weak_address::<T>()
+            .map(|addr| addr.queued())
+            .unwrap_or_default();
+
+        Some(Probe::new(sink, env, queue_depth))
+    }
+
+    fn schedule(&mut self, fut: impl Future<Output = ()> + Send + 'static) -> TimerHandle {
+        let (handle, registration) = AbortHandle::new_pair();
+        self.scheduled
+            .push(Abortable::new(fut, registration).map(|_| ()).boxed());
+
+        TimerHandle::new(handle)
+    }
+
+    /// Drives any scheduled messages.
+    fn poll_scheduled(&mut self, cx: &mut std::task::Context<'_>) {
+        while let Poll::Ready(Some(())) = self.scheduled.poll_next_unpin(cx) {}
+    }
+
+    /// Polls the attached streams and the mailbox for the next event to handle, alternating
+    /// which is polled first.
+    ///
+    /// Returns `None` once the mailbox is closed.
+    fn poll_next<M, T>(
+        &mut self,
+        mailbox: &mut M,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Event<A, T>>>
+    where
+        M: Inbox<T>,
+        T: Message,
+    {
+        self.streams_first = !self.streams_first;
+        if self.streams_first {
+            if let Poll::Ready(Some(event)) = self.streams.poll_next_unpin(cx) {
+                return Poll::Ready(Some(Event::Stream(event)));
+            }
+            mailbox
+                .poll_next_unpin(cx)
+                .map(|env| env.map(Event::Envelope))
+        } else {
+            if let Poll::Ready(env) = mailbox.poll_next_unpin(cx) {
+                return Poll::Ready(env.map(Event::Envelope));
+            }
+            // The attached streams are exhausted when there are none, which is not the end of
+            // the actor.
+            match self.streams.poll_next_unpin(cx) {
+                Poll::Ready(Some(event)) => Poll::Ready(Some(Event::Stream(event))),
+                _ => Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<A: Actor> Context<A> {
+    /// Polls the attached streams and a set of mailboxes for the next event to handle,
+    /// alternating which is polled first, see [`Context::poll_next`].
+    ///
+    /// Returns `None` once every mailbox is closed.
+    fn poll_next_many(
+        &mut self,
+        mailboxes: &mut MailboxSet<A>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Box<dyn StreamEvent<A>>>> {
+        self.streams_first = !self.streams_first;
+        if self.streams_first {
+            if let Poll::Ready(Some(event)) = self.streams.poll_next_unpin(cx) {
+                return Poll::Ready(Some(event));
+            }
+            mailboxes.poll_next(cx)
+        } else {
+            if let Poll::Ready(event) = mailboxes.poll_next(cx) {
+                return Poll::Ready(event);
+            }
+            match self.streams.poll_next_unpin(cx) {
+                Poll::Ready(Some(event)) => Poll::Ready(Some(event)),
+                _ => Poll::Pending,
+            }
+        }
+    }
+}
+
+/// The next event for an actor to handle.
+enum Event<A: Actor, T: Message> {
+    /// A message from the mailbox.
+    Envelope(Envelope<T>),
+    /// An event of an attached stream.
+    Stream(Box<dyn StreamEvent<A>>),
 }
 
 /// Runs an actor until it receives a stop signal or an error occurs.
@@ -205,18 +737,403 @@ where
     M: Stream<Item = Envelope<T>> + Unpin,
     T: Dispatch<A>,
 {
-    let mut ctx = Context::default();
+    run_with_context(actor, &mut StreamInbox(mailbox), Context::default()).await
+}
+
+/// Runs an actor with several mailboxes until it receives a stop signal, an error occurs,
+/// or every mailbox is closed.
+///
+/// The mailboxes may receive different types of messages, and are selected according to the
+/// policy of the set, see [`SelectPolicy`]. Messages are handled one at a time. See
+/// [`Runner::run_many`] to configure how the actor is run.
+///
+/// # Arguments
+///
+/// * `actor` - The actor to run.
+/// * `mailboxes` - The mailboxes which will be used to receive messages.
+pub async fn run_many<A>(actor: &mut A, mailboxes: &mut MailboxSet<A>) -> Result<A::Stop, A::Error>
+where
+    A: Actor,
+{
+    run_many_with_context(actor, mailboxes, Context::default()).await
+}
+
+pub(crate) async fn run_many_with_context<A>(
+    actor: &mut A,
+    mailboxes: &mut MailboxSet<A>,
+    mut ctx: Context<A>,
+) -> Result<A::Stop, A::Error>
+where
+    A: Actor,
+{
+    actor.started(&mut ctx)?;
+
+    while let Some(event) = poll_fn(|cx| {
+        ctx.poll_scheduled(cx);
+        ctx.poll_next_many(mailboxes, cx)
+    })
+    .await
+    {
+        handle_stream_event(actor, event, &mut ctx).await;
+
+        if let Some(err) = ctx.take_error() {
+            return Err(err);
+        } else if ctx.stopped() {
+            match ctx.stop_mode {
+                StopMode::Drop => {}
+                StopMode::Drain => {
+                    mailboxes.close();
+                    while let Some(event) = mailboxes.try_next() {
+                        handle_stream_event(actor, event, &mut ctx).await;
+
+                        if let Some(err) = ctx.take_error() {
+                            return Err(err);
+                        }
+                    }
+                }
+                StopMode::Reject => {
+                    mailboxes.close();
+                    mailboxes.reject();
+                }
+            }
+            break;
+        }
+    }
+
+    actor.stopped().await
+}
+
+/// Spawns an actor with a default [`Runner`], returning a handle to it.
+///
+/// See [`Runner::spawn`].
+///
+/// # Arguments
+///
+/// * `actor` - The actor to run.
+/// * `mailbox` - The mailbox which will be used to receive messages.
+/// * `spawner` - The spawner which runs the actor, see [`spawner`].
+pub fn spawn<A, T>(
+    actor: A,
+    mailbox: Mailbox<T>,
+    spawner: &impl spawner::Spawner,
+) -> ActorHandle<A, T>
+where
+    A: Actor + 'static,
+    A::Stop: Send + 'static,
+    T: Dispatch<A>,
+{
+    Runner::new().spawn(actor, mailbox, spawner)
+}
+
+pub(crate) async fn run_with_context<A, M, T>(
+    actor: &mut A,
+    mailbox: &mut M,
+    mut ctx: Context<A>,
+) -> Result<A::Stop, A::Error>
+where
+    A: Actor,
+    M: Inbox<T>,
+    T: Dispatch<A>,
+{
     actor.started(&mut ctx)?;
 
-    while let Some(env) = mailbox.next().await {
-        env.dispatch(actor, &mut ctx).await;
+    let mut mailbox = DeferInbox::new(mailbox);
+    while let Some(next) = poll_fn(|cx| {
+        ctx.poll_scheduled(cx);
+        ctx.poll_next(&mut mailbox, cx)
+    })
+    .await
+    {
+        match next {
+            Event::Envelope(env) => dispatch_next(actor, env, &mut mailbox, &mut ctx).await,
+            Event::Stream(event) => handle_stream_event(actor, event, &mut ctx).await,
+        }
 
         if let Some(err) = ctx.take_error() {
             return Err(err);
         } else if ctx.stopped() {
+            shutdown(actor, &mut mailbox, &mut ctx).await?;
             break;
         }
     }
 
     actor.stopped().await
 }
+
+/// Runs an actor until it receives a stop signal or an error occurs, handling concurrent
+/// messages concurrently.
+///
+/// Messages which are dispatched concurrently, see [`ConcurrentHandler`], are handled
+/// concurrently with each other up to `limit` at a time. When a message which requires
+/// exclusive access is received, the loop waits for all in-flight messages to be handled
+/// before handling it.
+///
+/// # Arguments
+///
+/// * `actor` - The actor to run.
+/// * `mailbox` - The mailbox which will be used to receive messages.
+/// * `limit` - The maximum number of messages to handle concurrently.
+pub async fn run_concurrent<A, M, T>(
+    actor: &mut A,
+    mailbox: &mut M,
+    limit: usize,
+) -> Result<A::Stop, A::Error>
+where
+    A: Actor + Sync,
+    M: Stream<Item = Envelope<T>> + Unpin,
+    T: Dispatch<A>,
+{
+    run_concurrent_with_context(actor, &mut StreamInbox(mailbox), Context::default(), limit).await
+}
+
+pub(crate) async fn run_concurrent_with_context<A, M, T>(
+    actor: &mut A,
+    mailbox: &mut M,
+    mut ctx: Context<A>,
+    limit: usize,
+) -> Result<A::Stop, A::Error>
+where
+    A: Actor + Sync,
+    M: Inbox<T>,
+    T: Dispatch<A>,
+{
+    actor.started(&mut ctx)?;
+
+    let mut mailbox = DeferInbox::new(mailbox);
+    let limit = limit.max(1);
+    loop {
+        let catch_panics = ctx.catch_panics;
+        let (next, panics) = {
+            let shared: &A = actor;
+            let mut inflight = FuturesUnordered::new();
+            let mut panics = Vec::new();
+
+            // Returns `None` if a message panicked, so the actor can decide how to proceed.
+            let next = poll_fn(|cx| {
+                ctx.poll_scheduled(cx);
+                loop {
+                    while let Poll::Ready(Some(res)) = inflight.poll_next_unpin(cx) {
+                        if let Err(message) = res {
+                            panics.push(message);
+                        }
+                    }
+
+                    if !panics.is_empty() {
+                        return Poll::Ready(None);
+                    } else if inflight.len() >= limit {
+                        return Poll::Pending;
+                    }
+
+                    match ready!(ctx.poll_next(&mut mailbox, cx)) {
+                        Some(Event::Envelope(env)) if ctx.skip_canceled && env.is_canceled() => {}
+                        Some(Event::Envelope(env)) => {
+                            let probe = ctx.probe(&env);
+                            let layers = ctx.layers::<T>();
+                            let handled = if catch_panics {
+                                env.dispatch_concurrent_catch_unwind_with_layers(shared, layers)
+                                    .map(Either::Left)
+                            } else {
+                                env.dispatch_concurrent_with_layers(shared, layers)
+                                    .map(|handled| Either::Right(handled.map(Ok)))
+                            };
+
+                            match handled {
+                                Ok(handled) => inflight.push(async move {
+                                    let res = handled.await;
+
+                                    if let Some(probe) = probe {
+                                        probe.finish();
+                                    }
+
+                                    res
+                                }),
+                                // The message is handled once the actor is no longer shared.
+                                Err(env) => return Poll::Ready(Some(Some(Event::Envelope(env)))),
+                            }
+                        }
+                        next => return Poll::Ready(Some(next)),
+                    }
+                }
+            })
+            .await;
+
+            // Wait for all in-flight messages before handling the next one exclusively.
+            poll_fn(|cx| {
+                ctx.poll_scheduled(cx);
+                while let Some(res) = ready!(inflight.poll_next_unpin(cx)) {
+                    if let Err(message) = res {
+                        panics.push(message);
+                    }
+                }
+                Poll::Ready(())
+            })
+            .await;
+
+            (next, panics)
+        };
+
+        for message in panics {
+            actor.panicked(&message, &mut ctx);
+        }
+
+        if ctx.stopped() || ctx.error().is_some() {
+            // The actor stopped before the received message could be handled.
+            if let Some(Some(Event::Envelope(env))) = next {
+                env.reject(Error::Stopped);
+            }
+        } else {
+            match next {
+                Some(Some(Event::Envelope(env))) => {
+                    dispatch_next(actor, env, &mut mailbox, &mut ctx).await
+                }
+                Some(Some(Event::Stream(event))) => {
+                    handle_stream_event(actor, event, &mut ctx).await
+                }
+                Some(None) => break,
+                None => {}
+            }
+        }
+
+        if let Some(err) = ctx.take_error() {
+            return Err(err);
+        } else if ctx.stopped() {
+            shutdown(actor, &mut mailbox, &mut ctx).await?;
+            break;
+        }
+    }
+
+    actor.stopped().await
+}
+
+/// Dispatches a message to the actor, in a batch with the messages queued behind it if it
+/// can be handled in a batch, see [`BatchHandler`].
+///
+/// The first queued message which can not be added to the batch is deferred, so it is
+/// received next.
+async fn dispatch_next<A, M, T>(
+    actor: &mut A,
+    env: Envelope<T>,
+    mailbox: &mut DeferInbox<'_, M, T>,
+    ctx: &mut Context<A>,
+) where
+    A: Actor,
+    M: Inbox<T>,
+    T: Dispatch<A>,
+{
+    let Some(key) = env.batch_key() else {
+        return dispatch(actor, env, ctx).await;
+    };
+
+    let mut batch = vec![env];
+    while batch.len() < ctx.max_batch_size {
+        match mailbox.next().now_or_never() {
+            Some(Some(env)) if env.batch_key() == Some(key) => batch.push(env),
+            Some(Some(env)) => {
+                mailbox.defer(env);
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    if ctx.skip_canceled {
+        batch.retain(|env| !env.is_canceled());
+        if batch.is_empty() {
+            return;
+        }
+    }
+
+    let probes = batch.iter().map(|env| ctx.probe(env)).collect::<Vec<_>>();
+
+    if ctx.catch_panics {
+        if let Err(message) = Envelope::dispatch_batch_catch_unwind(batch, actor, ctx).await {
+            actor.panicked(&message, ctx);
+        }
+    } else {
+        Envelope::dispatch_batch(batch, actor, ctx).await;
+    }
+
+    for probe in probes.into_iter().flatten() {
+        probe.finish();
+    }
+}
+
+/// Dispatches a message to the actor, catching any panic if enabled.
+async fn dispatch<A, T>(actor: &mut A, env: Envelope<T>, ctx: &mut Context<A>)
+where
+    A: Actor,
+    T: Dispatch<A>,
+{
+    if ctx.skip_canceled && env.is_canceled() {
+        return;
+    }
+
+    let probe = ctx.probe(&env);
+
+    if ctx.catch_panics {
+        if let Err(message) = env.dispatch_catch_unwind(actor, ctx).await {
+            actor.panicked(&message, ctx);
+        }
+    } else {
+        env.dispatch(actor, ctx).await;
+    }
+
+    if let Some(probe) = probe {
+        probe.finish();
+    }
+}
+
+/// Handles an event of an attached stream, catching any panic if enabled.
+async fn handle_stream_event<A: Actor>(
+    actor: &mut A,
+    event: Box<dyn StreamEvent<A>>,
+    ctx: &mut Context<A>,
+) {
+    if ctx.catch_panics {
+        let res = AssertUnwindSafe(event.handle(actor, ctx))
+            .catch_unwind()
+            .await;
+        if let Err(payload) = res {
+            actor.panicked(&envelope::panic_message(payload), ctx);
+        }
+    } else {
+        event.handle(actor, ctx).await;
+    }
+}
+
+/// Handles any queued messages according to the context's [`StopMode`].
+///
+/// Queued messages are drained like they would be handled while running, but not
+/// concurrently.
+async fn shutdown<A, M, T>(
+    actor: &mut A,
+    mailbox: &mut DeferInbox<'_, M, T>,
+    ctx: &mut Context<A>,
+) -> Result<(), A::Error>
+where
+    A: Actor,
+    M: Inbox<T>,
+    T: Dispatch<A>,
+{
+    match ctx.stop_mode {
+        StopMode::Drop => {}
+        StopMode::Drain => {
+            mailbox.close();
+            while let Some(Some(env)) = mailbox.next().now_or_never() {
+                dispatch_next(actor, env, mailbox, ctx).await;
+
+                if let Some(err) = ctx.take_error() {
+                    return Err(err);
+                }
+            }
+        }
+        StopMode::Reject => {
+            mailbox.close();
+            while let Some(Some(env)) = mailbox.next().now_or_never() {
+                env.reject(Error::Stopped);
+            }
+        }
+    }
+
+    Ok(())
+}
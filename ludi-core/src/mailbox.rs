@@ -7,38 +7,218 @@ use futures_core::Stream;
 use futures_util::StreamExt;
 
 use crate::{
-    channel::{new_channel, new_unbounded_channel, Receiver},
-    Address, Envelope, Message,
+    address::WeakAddress,
+    channel::{new_channel, new_priority_channel, new_unbounded_channel, Receiver},
+    Address, Envelope, Error, Message, PriorityPolicy,
 };
 
 /// Returns a new mailbox and address.
 pub fn mailbox<T: Message>(capacity: usize) -> (Mailbox<T>, Address<T>) {
     let (sender, recv) = new_channel(capacity);
+    let addr = Address::new(sender);
 
-    (Mailbox { recv }, Address::new(sender))
+    (Mailbox::new(recv, addr.downgrade()), addr)
 }
 
 /// Returns a new unbounded mailbox and address.
 pub fn unbounded_mailbox<T: Message>() -> (Mailbox<T>, Address<T>) {
     let (sender, recv) = new_unbounded_channel();
+    let addr = Address::new(sender);
 
-    (Mailbox { recv }, Address::new(sender))
+    (Mailbox::new(recv, addr.downgrade()), addr)
+}
+
+/// Returns a new mailbox with multiple priority lanes, and its address.
+///
+/// Messages are received from the highest lane with a queued message first, using the
+/// default [`PriorityPolicy`] so lower lanes still make progress. The lane of a message is
+/// determined by its [`Priority`](crate::Priority).
+///
+/// # Arguments
+///
+/// * `capacity` - The capacity of each lane.
+/// * `lanes` - The number of lanes.
+///
+/// # Panics
+///
+/// Panics if `lanes` is zero.
+pub fn priority_mailbox<T: Message>(capacity: usize, lanes: usize) -> (Mailbox<T>, Address<T>) {
+    priority_mailbox_with_policy(capacity, lanes, PriorityPolicy::default())
+}
+
+/// Returns a new mailbox with multiple priority lanes, and its address, using the given
+/// policy to choose the next message.
+///
+/// See [`priority_mailbox`].
+///
+/// # Panics
+///
+/// Panics if `lanes` is zero.
+pub fn priority_mailbox_with_policy<T: Message>(
+    capacity: usize,
+    lanes: usize,
+    policy: PriorityPolicy,
+) -> (Mailbox<T>, Address<T>) {
+    let (sender, recv) = new_priority_channel(capacity, lanes, policy);
+    let addr = Address::new(sender);
+
+    (Mailbox::new(recv, addr.downgrade()), addr)
 }
 
 /// A mailbox.
 pub struct Mailbox<T: Message> {
     recv: Receiver<T>,
+    addr: WeakAddress<T>,
+}
+
+impl<T: Message> Mailbox<T> {
+    fn new(recv: Receiver<T>, addr: WeakAddress<T>) -> Self {
+        Self { recv, addr }
+    }
+
+    /// Returns a weak address to this mailbox.
+    pub(crate) fn weak_address(&self) -> WeakAddress<T> {
+        self.addr.clone()
+    }
+
+    /// Closes the mailbox, preventing any new messages from being sent.
+    ///
+    /// Messages which are already queued can still be received. Any attempt to send a new
+    /// message will fail with [`Error::Stopped`].
+    pub fn close(&mut self) {
+        self.addr.set_stopped();
+        self.recv.close();
+    }
+
+    /// Returns the number of messages queued in the mailbox.
+    pub fn len(&self) -> usize {
+        self.addr.queued()
+    }
+
+    /// Returns `true` if there are no messages queued in the mailbox.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Message> Drop for Mailbox<T> {
+    fn drop(&mut self) {
+        // Notify the senders of any messages which were never handled.
+        self.recv.close();
+        while let Some(env) = self.recv.try_next() {
+            env.reject(Error::Stopped);
+        }
+        self.addr.set_closed();
+    }
 }
 
 impl<T: Message> Stream for Mailbox<T> {
     type Item = Envelope<T>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.recv.poll_next_unpin(cx)
+        let poll = self.recv.poll_next_unpin(cx);
+        if let Poll::Ready(Some(_)) = poll {
+            self.addr.set_received();
+        }
+        poll
+    }
+}
+
+/// A stream of envelopes which an actor receives messages from.
+pub(crate) trait Inbox<T: Message>: Stream<Item = Envelope<T>> + Unpin {
+    /// Closes the inbox, preventing any new messages from being sent.
+    fn close(&mut self) {}
+}
+
+impl<T: Message> Inbox<T> for Mailbox<T> {
+    fn close(&mut self) {
+        Mailbox::close(self)
+    }
+}
+
+/// An [`Inbox`] for any stream of envelopes.
+pub(crate) struct StreamInbox<'a, M>(pub(crate) &'a mut M);
+
+impl<M, T> Stream for StreamInbox<'_, M>
+where
+    M: Stream<Item = Envelope<T>> + Unpin,
+    T: Message,
+{
+    type Item = Envelope<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_next_unpin(cx)
+    }
+}
+
+impl<M, T> Inbox<T> for StreamInbox<'_, M>
+where
+    M: Stream<Item = Envelope<T>> + Unpin,
+    T: Message,
+{
+}
+
+/// An [`Inbox`] which can defer an envelope, so it is received again next.
+pub(crate) struct DeferInbox<'a, M, T: Message> {
+    inbox: &'a mut M,
+    deferred: Option<Envelope<T>>,
+}
+
+impl<'a, M, T: Message> DeferInbox<'a, M, T> {
+    pub(crate) fn new(inbox: &'a mut M) -> Self {
+        Self {
+            inbox,
+            deferred: None,
+        }
+    }
+
+    /// Defers an envelope which was received, so it is the next envelope to be received.
+    pub(crate) fn defer(&mut self, env: Envelope<T>) {
+        debug_assert!(self.deferred.is_none(), "only one envelope can be deferred");
+        self.deferred = Some(env);
+    }
+}
+
+impl<M, T> Stream for DeferInbox<'_, M, T>
+where
+    M: Inbox<T>,
+    T: Message,
+{
+    type Item = Envelope<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(env) = self.deferred.take() {
+            return Poll::Ready(Some(env));
+        }
+
+        self.inbox.poll_next_unpin(cx)
+    }
+}
+
+impl<M, T> Inbox<T> for DeferInbox<'_, M, T>
+where
+    M: Inbox<T>,
+    T: Message,
+{
+    fn close(&mut self) {
+        self.inbox.close()
+    }
+}
+
+impl<M, T: Message> Drop for DeferInbox<'_, M, T> {
+    fn drop(&mut self) {
+        // The deferred envelope was already taken from the mailbox, so it is not rejected
+        // along with the queued envelopes.
+        if let Some(env) = self.deferred.take() {
+            env.reject(Error::Stopped);
+        }
     }
 }
 
 /// An extension trait which converts a stream of messages into a mailbox.
+///
+/// To handle the items of a stream along with the messages of a mailbox instead, see
+/// [`Context::add_stream`](crate::Context::add_stream).
 pub trait IntoMailbox: Sized {
     /// Convert self into a mailbox.
     fn into_mailbox(self) -> IntoMail<Self>;
@@ -0,0 +1,225 @@
+//! Instrumentation of actors.
+//!
+//! Metrics are opt-in, see [`Runner::with_metrics`](crate::Runner::with_metrics). Every
+//! handled message produces a [`Record`] which is passed to a [`MetricsSink`]. [`Metrics`] is
+//! a sink which aggregates the records, so they can be inspected with [`Metrics::snapshot`].
+//!
+//! Messages are identified by their [`label`](crate::Message::label). Wrapper messages
+//! derived with `Wrap` are labeled with the name of their variant.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{Envelope, Message};
+
+/// A sink which records metrics of an actor.
+pub trait MetricsSink: Send + Sync + 'static {
+    /// Records a message which has been handled.
+    fn record(&self, record: &Record);
+}
+
+/// A record of a message which has been handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record {
+    label: &'static str,
+    queue_depth: usize,
+    wait: Duration,
+    duration: Duration,
+}
+
+impl Record {
+    /// Returns the label of the message, see [`Message::label`].
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Returns the number of messages which were queued when the message was received.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Returns the time between the message being sent and it being dispatched.
+    ///
+    /// Zero if the message was sent before the actor started recording metrics.
+    pub fn wait(&self) -> Duration {
+        self.wait
+    }
+
+    /// Returns the time it took to handle the message.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/// Measures the handling of a single message.
+pub(crate) struct Probe {
+    sink: Arc<dyn MetricsSink>,
+    label: &'static str,
+    queue_depth: usize,
+    wait: Duration,
+    start: Instant,
+}
+
+impl Probe {
+    pub(crate) fn new<T: Message>(
+        sink: Arc<dyn MetricsSink>,
+        env: &Envelope<T>,
+        queue_depth: usize,
+    ) -> Self {
+        let start = Instant::now();
+        Self {
+            sink,
+            label: env.label(),
+            queue_depth,
+            wait: env
+                .created()
+                .map(|created| start.saturating_duration_since(created))
+                .unwrap_or_default(),
+            start,
+        }
+    }
+
+    /// Records the message as handled.
+    pub(crate) fn finish(self) {
+        self.sink.record(&Record {
+            label: self.label,
+            queue_depth: self.queue_depth,
+            wait: self.wait,
+            duration: self.start.elapsed(),
+        });
+    }
+}
+
+/// A [`MetricsSink`] which aggregates records.
+///
+/// Metrics is a cheap handle, clones of it share the same records.
+#[derive(Debug, Default, Clone)]
+pub struct Metrics(Arc<Mutex<MetricsSnapshot>>);
+
+impl Metrics {
+    /// Creates a new, empty, set of metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a snapshot of the metrics.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.lock().clone()
+    }
+
+    /// Clears the metrics.
+    pub fn reset(&self) {
+        *self.lock() = MetricsSnapshot::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MetricsSnapshot> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl MetricsSink for Metrics {
+    fn record(&self, record: &Record) {
+        let mut snapshot = self.lock();
+        snapshot.processed += 1;
+        snapshot.queue_depth = record.queue_depth;
+        snapshot.max_queue_depth = snapshot.max_queue_depth.max(record.queue_depth);
+
+        let message = snapshot.messages.entry(record.label).or_default();
+        message.count += 1;
+        message.total_wait += record.wait;
+        message.max_wait = message.max_wait.max(record.wait);
+        message.total_duration += record.duration;
+        message.max_duration = message.max_duration.max(record.duration);
+    }
+}
+
+/// A snapshot of [`Metrics`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    processed: u64,
+    queue_depth: usize,
+    max_queue_depth: usize,
+    messages: HashMap<&'static str, MessageMetrics>,
+}
+
+impl MetricsSnapshot {
+    /// Returns the number of messages which have been handled.
+    pub fn processed(&self) -> u64 {
+        self.processed
+    }
+
+    /// Returns the number of messages which were queued when the last message was received.
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth
+    }
+
+    /// Returns the largest number of messages which were queued when a message was received.
+    pub fn max_queue_depth(&self) -> usize {
+        self.max_queue_depth
+    }
+
+    /// Returns the metrics of messages with the given label.
+    pub fn message(&self, label: &str) -> Option<&MessageMetrics> {
+        self.messages.get(label)
+    }
+
+    /// Returns an iterator over the metrics of every message label.
+    pub fn messages(&self) -> impl Iterator<Item = (&'static str, &MessageMetrics)> {
+        self.messages
+            .iter()
+            .map(|(label, metrics)| (*label, metrics))
+    }
+}
+
+/// Metrics of the messages with a single label.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MessageMetrics {
+    count: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+    total_duration: Duration,
+    max_duration: Duration,
+}
+
+impl MessageMetrics {
+    /// Returns the number of messages which have been handled.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the mean time between a message being sent and it being dispatched.
+    pub fn mean_wait(&self) -> Duration {
+        mean(self.total_wait, self.count)
+    }
+
+    /// Returns the longest time between a message being sent and it being dispatched.
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    /// Returns the mean time it took to handle a message.
+    pub fn mean_duration(&self) -> Duration {
+        mean(self.total_duration, self.count)
+    }
+
+    /// Returns the longest time it took to handle a message.
+    pub fn max_duration(&self) -> Duration {
+        self.max_duration
+    }
+
+    /// Returns the total time spent handling messages.
+    pub fn total_duration(&self) -> Duration {
+        self.total_duration
+    }
+}
+
+fn mean(total: Duration, count: u64) -> Duration {
+    if count == 0 {
+        Duration::ZERO
+    } else {
+        Duration::from_nanos((total.as_nanos() / count as u128) as u64)
+    }
+}
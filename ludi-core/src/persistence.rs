@@ -0,0 +1,555 @@
+//! Event-sourced persistence of actors.
+//!
+//! An [`EventSourced`] actor changes its state only by applying events. Each event is first
+//! appended to a [`Journal`] with [`EventSourced::persist`], and on startup the state is
+//! rebuilt from the journal with [`EventSourced::recover`], eg. in [`Actor::started`].
+//!
+//! Snapshots of the state are saved periodically, see [`EventSourced::snapshot_interval`], so
+//! only the events after the latest snapshot are replayed.
+//!
+//! Events and snapshots are written asynchronously, so a journal can wait for slow storage
+//! without blocking the executor. Journals are loaded synchronously, as recovery happens in
+//! [`Actor::started`]. [`InMemoryJournal`] keeps the events in memory and `FileJournal`
+//! appends them to a file from a dedicated thread, which requires the `serde` feature.
+
+use std::{
+    fmt::Display,
+    future::{self, Future},
+    num::NonZeroU64,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::Actor;
+
+#[cfg(feature = "serde")]
+pub use file::FileJournal;
+
+/// An actor whose state is rebuilt from a journal of events.
+pub trait EventSourced: Actor {
+    /// The type of events which change the state of the actor.
+    type Event: Send + Sync + 'static;
+    /// The type of snapshots of the state of the actor.
+    type Snapshot: Send + Sync + 'static;
+    /// The journal which the events are persisted to.
+    type Journal: Journal<Event = Self::Event, Snapshot = Self::Snapshot>;
+
+    /// Returns the journal of the actor.
+    fn journal(&mut self) -> &mut Self::Journal;
+
+    /// Applies an event to the state of the actor.
+    ///
+    /// This method is called both for new events and for events replayed from the journal,
+    /// so it should not have side effects other than changing the state.
+    fn apply(&mut self, event: &Self::Event);
+
+    /// Returns a snapshot of the state of the actor.
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Restores the state of the actor from a snapshot.
+    fn restore(&mut self, snapshot: Self::Snapshot);
+
+    /// Returns the number of events after which a snapshot is saved.
+    ///
+    /// By default this returns `None`, and snapshots are never saved.
+    fn snapshot_interval(&self) -> Option<NonZeroU64> {
+        None
+    }
+
+    /// A method which is called when a snapshot can not be saved.
+    ///
+    /// The event which triggered the snapshot has already been persisted and applied, so a
+    /// failed snapshot only means more events are replayed on recovery. By default the error
+    /// is ignored, and the next snapshot is attempted after another
+    /// [`EventSourced::snapshot_interval`] events.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error which occurred when saving the snapshot.
+    fn snapshot_failed(&mut self, err: JournalError) {
+        let _ = err;
+    }
+
+    /// Persists an event to the journal and then applies it to the state of the actor.
+    ///
+    /// If the event can not be persisted it is not applied, and an error is returned. A
+    /// snapshot is saved once every [`EventSourced::snapshot_interval`] events, if that fails
+    /// the event is still persisted, see [`EventSourced::snapshot_failed`].
+    fn persist(
+        &mut self,
+        event: Self::Event,
+    ) -> impl Future<Output = Result<(), JournalError>> + Send {
+        async move {
+            let seq = self.journal().append(&event).await?;
+            self.apply(&event);
+
+            if let Some(interval) = self.snapshot_interval() {
+                if seq % interval.get() == 0 {
+                    let snapshot = self.snapshot();
+                    if let Err(err) = self.journal().save_snapshot(seq, &snapshot).await {
+                        self.snapshot_failed(err);
+                    }
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Rebuilds the state of the actor from the journal.
+    ///
+    /// The latest snapshot is restored, if any, and the events persisted after it are
+    /// applied in order.
+    fn recover(&mut self) -> Result<(), JournalError> {
+        let (snapshot, events) = self.journal().load()?.into_parts();
+        if let Some(snapshot) = snapshot {
+            self.restore(snapshot);
+        }
+        for event in &events {
+            self.apply(event);
+        }
+
+        Ok(())
+    }
+}
+
+/// A persistent log of events, see [`EventSourced`].
+///
+/// Events are numbered by their sequence in the journal, starting at 1.
+///
+/// Writes are asynchronous, and should not block the executor while they wait for storage,
+/// eg. by performing blocking I/O on another thread.
+pub trait Journal: Send {
+    /// The type of events in the journal.
+    type Event;
+    /// The type of snapshots in the journal.
+    type Snapshot;
+
+    /// Appends an event to the journal, returning its sequence number.
+    fn append(
+        &mut self,
+        event: &Self::Event,
+    ) -> impl Future<Output = Result<u64, JournalError>> + Send;
+
+    /// Saves a snapshot of the state after the event with sequence number `seq`, replacing
+    /// any previous snapshot.
+    fn save_snapshot(
+        &mut self,
+        seq: u64,
+        snapshot: &Self::Snapshot,
+    ) -> impl Future<Output = Result<(), JournalError>> + Send;
+
+    /// Loads the latest snapshot and the events persisted after it.
+    fn load(&mut self) -> Result<Recovery<Self::Event, Self::Snapshot>, JournalError>;
+}
+
+/// The contents of a journal which are needed to rebuild the state of an actor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery<E, S> {
+    snapshot: Option<S>,
+    events: Vec<E>,
+}
+
+impl<E, S> Recovery<E, S> {
+    /// Creates a new recovery from a snapshot and the events persisted after it.
+    pub fn new(snapshot: Option<S>, events: Vec<E>) -> Self {
+        Self { snapshot, events }
+    }
+
+    /// Returns the snapshot.
+    pub fn snapshot(&self) -> Option<&S> {
+        self.snapshot.as_ref()
+    }
+
+    /// Returns the events persisted after the snapshot.
+    pub fn events(&self) -> &[E] {
+        &self.events
+    }
+
+    /// Returns the snapshot and the events.
+    pub fn into_parts(self) -> (Option<S>, Vec<E>) {
+        (self.snapshot, self.events)
+    }
+}
+
+/// Errors that can occur when accessing a journal.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JournalError {
+    /// An I/O error occurred.
+    Io(std::io::Error),
+    /// An event or snapshot could not be encoded or decoded.
+    Encoding(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalError::Io(err) => write!(f, "journal i/o error: {}", err),
+            JournalError::Encoding(err) => write!(f, "journal encoding error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JournalError::Io(err) => Some(err),
+            JournalError::Encoding(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl From<std::io::Error> for JournalError {
+    fn from(err: std::io::Error) -> Self {
+        JournalError::Io(err)
+    }
+}
+
+struct Entries<E, S> {
+    events: Vec<E>,
+    snapshot: Option<(u64, S)>,
+}
+
+/// A journal which keeps the events in memory.
+///
+/// The journal is a cheap handle, clones of it share the same events. This allows a
+/// restarted actor to recover the state of its predecessor.
+pub struct InMemoryJournal<E, S> {
+    entries: Arc<Mutex<Entries<E, S>>>,
+}
+
+impl<E, S> Default for InMemoryJournal<E, S> {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(Entries {
+                events: Vec::new(),
+                snapshot: None,
+            })),
+        }
+    }
+}
+
+impl<E, S> Clone for InMemoryJournal<E, S> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<E, S> std::fmt::Debug for InMemoryJournal<E, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InMemoryJournal")
+            .field("events", &self.len())
+            .field("snapshot_seq", &self.snapshot_seq())
+            .finish()
+    }
+}
+
+impl<E, S> InMemoryJournal<E, S> {
+    /// Creates a new empty journal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries<E, S>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns the number of events in the journal.
+    pub fn len(&self) -> usize {
+        self.lock().events.len()
+    }
+
+    /// Returns `true` if there are no events in the journal.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the sequence number of the event after which the latest snapshot was saved.
+    pub fn snapshot_seq(&self) -> Option<u64> {
+        self.lock().snapshot.as_ref().map(|(seq, _)| *seq)
+    }
+}
+
+impl<E, S> Journal for InMemoryJournal<E, S>
+where
+    E: Clone + Send,
+    S: Clone + Send,
+{
+    type Event = E;
+    type Snapshot = S;
+
+    fn append(&mut self, event: &E) -> impl Future<Output = Result<u64, JournalError>> + Send {
+        let mut entries = self.lock();
+        entries.events.push(event.clone());
+        future::ready(Ok(entries.events.len() as u64))
+    }
+
+    fn save_snapshot(
+        &mut self,
+        seq: u64,
+        snapshot: &S,
+    ) -> impl Future<Output = Result<(), JournalError>> + Send {
+        self.lock().snapshot = Some((seq, snapshot.clone()));
+        future::ready(Ok(()))
+    }
+
+    fn load(&mut self) -> Result<Recovery<E, S>, JournalError> {
+        let entries = self.lock();
+        let (seq, snapshot) = match &entries.snapshot {
+            Some((seq, snapshot)) => (*seq, Some(snapshot.clone())),
+            None => (0, None),
+        };
+        let events = entries.events.iter().skip(seq as usize).cloned().collect();
+
+        Ok(Recovery::new(snapshot, events))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use std::{
+        ffi::OsString,
+        fs::{self, File, OpenOptions},
+        future::Future,
+        io::{self, BufRead, BufReader, ErrorKind, Write as _},
+        marker::PhantomData,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            mpsc, Arc,
+        },
+        thread,
+    };
+
+    use futures_channel::oneshot;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::{Journal, JournalError, Recovery};
+
+    /// The channel which the writer thread reports the result of a write on.
+    type Reply<T = ()> = oneshot::Sender<io::Result<T>>;
+
+    /// A write which is performed by the writer thread of a [`FileJournal`].
+    enum Write {
+        /// Appends an encoded event to the journal, replying with its sequence number.
+        Append(Vec<u8>, Reply<u64>),
+        /// Replaces the snapshot with an encoded snapshot.
+        Snapshot(Vec<u8>, Reply),
+    }
+
+    impl From<serde_json::Error> for JournalError {
+        fn from(err: serde_json::Error) -> Self {
+            JournalError::Encoding(Box::new(err))
+        }
+    }
+
+    /// A journal which appends the events to a file.
+    ///
+    /// Every event is encoded as a line of JSON, and is synced to disk before it is
+    /// applied. The latest snapshot is saved next to the journal, in a file with the
+    /// `.snapshot` suffix, which is replaced atomically.
+    ///
+    /// Events and snapshots are written by a dedicated thread, so the executor is not
+    /// blocked while they are synced to disk. The thread stops once the journal is dropped
+    /// and every pending write has been performed. The thread counts the events, so an
+    /// append is accounted for even if its future is dropped. Opening and loading the
+    /// journal read the file with blocking I/O.
+    #[derive(Debug)]
+    pub struct FileJournal<E, S> {
+        writer: mpsc::Sender<Write>,
+        path: PathBuf,
+        snapshot_path: PathBuf,
+        len: Arc<AtomicU64>,
+        _pd: PhantomData<fn() -> (E, S)>,
+    }
+
+    impl<E, S> FileJournal<E, S> {
+        /// Opens the journal at a path, creating the file if it does not exist.
+        ///
+        /// A final line which was only partially written, eg. because the process crashed
+        /// while appending it, is removed. Its event was never applied.
+        pub fn open(path: impl AsRef<Path>) -> Result<Self, JournalError> {
+            let path = path.as_ref().to_path_buf();
+            let file = OpenOptions::new()
+                .read(true)
+                .append(true)
+                .create(true)
+                .open(&path)?;
+
+            // The number of complete lines, and the length of the file they take up.
+            let (mut len, mut file_len) = (0, 0);
+            let mut reader = BufReader::new(&file);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line)? {
+                    0 => break,
+                    n if line.ends_with(b"\n") => {
+                        len += 1;
+                        file_len += n as u64;
+                    }
+                    _ => {
+                        file.set_len(file_len)?;
+                        file.sync_data()?;
+                        break;
+                    }
+                }
+            }
+
+            let mut snapshot_path = OsString::from(path.as_os_str());
+            snapshot_path.push(".snapshot");
+            let snapshot_path = PathBuf::from(snapshot_path);
+
+            let len = Arc::new(AtomicU64::new(len));
+            let (writer, writes) = mpsc::channel();
+            let thread_len = len.clone();
+            let thread_snapshot_path = snapshot_path.clone();
+            thread::Builder::new()
+                .name("ludi-journal".to_string())
+                .spawn(move || write(file, file_len, &thread_len, &thread_snapshot_path, writes))?;
+
+            Ok(Self {
+                writer,
+                path,
+                snapshot_path,
+                len,
+                _pd: PhantomData,
+            })
+        }
+
+        /// Returns the path of the journal.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        /// Returns the number of events in the journal.
+        pub fn len(&self) -> u64 {
+            self.len.load(Ordering::Acquire)
+        }
+
+        /// Returns `true` if there are no events in the journal.
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Sends a write to the writer thread, returning a future which resolves once it has
+        /// been performed.
+        fn send<T: Send + 'static>(
+            &self,
+            write: impl FnOnce(Reply<T>) -> Write,
+        ) -> impl Future<Output = io::Result<T>> + Send + 'static {
+            let (tx, rx) = oneshot::channel();
+            let sent = self.writer.send(write(tx)).is_ok();
+
+            async move {
+                if !sent {
+                    return Err(writer_stopped());
+                }
+                rx.await.unwrap_or_else(|_| Err(writer_stopped()))
+            }
+        }
+    }
+
+    impl<E, S> Journal for FileJournal<E, S>
+    where
+        E: Serialize + DeserializeOwned,
+        S: Serialize + DeserializeOwned,
+    {
+        type Event = E;
+        type Snapshot = S;
+
+        fn append(&mut self, event: &E) -> impl Future<Output = Result<u64, JournalError>> + Send {
+            let written = serde_json::to_vec(event).map(|mut line| {
+                line.push(b'\n');
+                self.send(|reply| Write::Append(line, reply))
+            });
+
+            async move { Ok(written?.await?) }
+        }
+
+        fn save_snapshot(
+            &mut self,
+            seq: u64,
+            snapshot: &S,
+        ) -> impl Future<Output = Result<(), JournalError>> + Send {
+            let written = serde_json::to_vec(&(seq, snapshot))
+                .map(|buf| self.send(|reply| Write::Snapshot(buf, reply)));
+
+            async move { Ok(written?.await?) }
+        }
+
+        fn load(&mut self) -> Result<Recovery<E, S>, JournalError> {
+            let (seq, snapshot) = match File::open(&self.snapshot_path) {
+                Ok(file) => {
+                    let (seq, snapshot): (u64, S) = serde_json::from_reader(BufReader::new(file))?;
+                    (seq, Some(snapshot))
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => (0, None),
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut events = Vec::new();
+            for line in BufReader::new(File::open(&self.path)?)
+                .lines()
+                .skip(seq as usize)
+            {
+                events.push(serde_json::from_str(&line?)?);
+            }
+
+            Ok(Recovery::new(snapshot, events))
+        }
+    }
+
+    /// Performs the writes of a journal until it is dropped.
+    fn write(
+        mut file: File,
+        mut file_len: u64,
+        len: &AtomicU64,
+        snapshot_path: &Path,
+        writes: mpsc::Receiver<Write>,
+    ) {
+        for write in writes {
+            match write {
+                Write::Append(line, reply) => {
+                    let seq = append_line(&mut file, &mut file_len, &line)
+                        .map(|()| len.fetch_add(1, Ordering::AcqRel) + 1);
+                    _ = reply.send(seq);
+                }
+                Write::Snapshot(buf, reply) => {
+                    _ = reply.send(replace_snapshot(snapshot_path, &buf));
+                }
+            }
+        }
+    }
+
+    /// Appends a line to the journal, removing any part of it which was written if it fails.
+    fn append_line(file: &mut File, file_len: &mut u64, line: &[u8]) -> io::Result<()> {
+        match file.write_all(line).and_then(|()| file.sync_data()) {
+            Ok(()) => {
+                *file_len += line.len() as u64;
+                Ok(())
+            }
+            Err(err) => {
+                // The next line must not be appended to a partial one.
+                _ = file.set_len(*file_len);
+                Err(err)
+            }
+        }
+    }
+
+    fn replace_snapshot(snapshot_path: &Path, buf: &[u8]) -> io::Result<()> {
+        let mut tmp_path = snapshot_path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(buf)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, snapshot_path)
+    }
+
+    fn writer_stopped() -> io::Error {
+        io::Error::new(ErrorKind::BrokenPipe, "journal writer stopped")
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{channel::BoundedReceiver, Envelope, Message};
+
+/// The priority of a message.
+///
+/// Priorities are only taken into account by mailboxes with more than one lane, see
+/// [`priority_mailbox`](crate::priority_mailbox). The range of priorities is divided evenly
+/// between the lanes of a mailbox, and messages in higher lanes are received first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Priority(u8);
+
+impl Priority {
+    /// The lowest priority.
+    pub const LOW: Self = Self(0);
+    /// The default priority.
+    pub const NORMAL: Self = Self(127);
+    /// The highest priority.
+    pub const HIGH: Self = Self(u8::MAX);
+
+    /// Creates a new priority, higher values are received first.
+    pub const fn new(priority: u8) -> Self {
+        Self(priority)
+    }
+
+    /// Returns the value of the priority.
+    pub const fn get(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns the lane of a mailbox with `lanes` lanes which this priority maps to.
+    pub(crate) fn lane(&self, lanes: usize) -> usize {
+        (self.0 as usize * lanes) >> 8
+    }
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// A policy which determines how a priority mailbox chooses the next message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityPolicy {
+    /// Always receive from the highest lane with a queued message.
+    ///
+    /// Lower lanes may starve if higher lanes are never empty.
+    Strict,
+    /// Receive from the highest lane with a queued message, but after every `n` messages
+    /// give a turn to the lower lanes so they still make progress.
+    ///
+    /// Turns are handed out round-robin, starting from the next lane below the highest.
+    MaxBurst(usize),
+}
+
+impl Default for PriorityPolicy {
+    fn default() -> Self {
+        Self::MaxBurst(16)
+    }
+}
+
+/// The receiving end of a mailbox with multiple lanes.
+#[derive(Debug)]
+pub(crate) struct PriorityReceiver<T: Message> {
+    /// Lanes, ordered from lowest to highest priority.
+    lanes: Vec<BoundedReceiver<T>>,
+    closed: Vec<bool>,
+    policy: PriorityPolicy,
+    /// The number of messages received since the last turn of the lower lanes.
+    burst: usize,
+    /// The lane which is given the next turn.
+    cursor: usize,
+}
+
+impl<T: Message> PriorityReceiver<T> {
+    pub(crate) fn new(lanes: Vec<BoundedReceiver<T>>, policy: PriorityPolicy) -> Self {
+        let count = lanes.len();
+        Self {
+            lanes,
+            closed: vec![false; count],
+            policy,
+            burst: 0,
+            cursor: count.saturating_sub(2),
+        }
+    }
+
+    pub(crate) fn close(&mut self) {
+        self.lanes.iter_mut().for_each(|lane| lane.close());
+    }
+
+    /// Returns the next message if one is immediately available.
+    pub(crate) fn try_next(&mut self) -> Option<Envelope<T>> {
+        self.lanes
+            .iter_mut()
+            .rev()
+            .find_map(|lane| lane.try_recv().ok())
+    }
+
+    /// Polls a single lane.
+    fn poll_lane(&mut self, lane: usize, cx: &mut Context<'_>) -> Poll<Option<Envelope<T>>> {
+        if self.closed[lane] {
+            return Poll::Ready(None);
+        }
+
+        let poll = Pin::new(&mut self.lanes[lane]).poll_next(cx);
+        if let Poll::Ready(None) = poll {
+            self.closed[lane] = true;
+        }
+
+        poll
+    }
+}
+
+impl<T: Message> Stream for PriorityReceiver<T> {
+    type Item = Envelope<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let count = this.lanes.len();
+
+        if let PriorityPolicy::MaxBurst(max) = this.policy {
+            if count > 1 && this.burst >= max {
+                // Give a turn to the lanes below the highest, starting from the cursor.
+                let start = this.cursor;
+                for lane in (0..count - 1).map(|i| (start + count - 1 - i) % (count - 1)) {
+                    if let Poll::Ready(Some(env)) = this.poll_lane(lane, cx) {
+                        this.burst = 0;
+                        this.cursor = (lane + count - 2) % (count - 1);
+                        return Poll::Ready(Some(env));
+                    }
+                }
+            }
+        }
+
+        let mut pending = false;
+        for lane in (0..count).rev() {
+            match this.poll_lane(lane, cx) {
+                Poll::Ready(Some(env)) => {
+                    this.burst += 1;
+                    return Poll::Ready(Some(env));
+                }
+                Poll::Ready(None) => {}
+                Poll::Pending => pending = true,
+            }
+        }
+
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(None)
+        }
+    }
+}
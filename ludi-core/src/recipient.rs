@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use futures_core::future::BoxFuture;
+use futures_util::FutureExt;
+
+use crate::{Address, Error, Message, Subscriber, Wrap};
+
+/// An address which is erased to the type of message it sends.
+trait AnyAddress<M: Message>: Send + Sync + 'static {
+    fn send(&self, msg: M) -> BoxFuture<'static, Result<M::Return, Error>>;
+
+    fn queue(&self, msg: M) -> BoxFuture<'static, Result<(), Error>>;
+
+    fn is_connected(&self) -> bool;
+
+    fn closed(&self) -> BoxFuture<'static, ()>;
+}
+
+impl<T, M> AnyAddress<M> for Address<T>
+where
+    T: Wrap<M>,
+    M: Message,
+{
+    fn send(&self, msg: M) -> BoxFuture<'static, Result<M::Return, Error>> {
+        self.wait(msg.into())
+            .map(|ret| T::unwrap_return(ret?))
+            .boxed()
+    }
+
+    fn queue(&self, msg: M) -> BoxFuture<'static, Result<(), Error>> {
+        Address::queue(self, msg.into()).boxed()
+    }
+
+    fn is_connected(&self) -> bool {
+        Address::is_connected(self)
+    }
+
+    fn closed(&self) -> BoxFuture<'static, ()> {
+        Address::closed(self).boxed()
+    }
+}
+
+/// An address which can send messages of type `M` to any actor which handles them.
+///
+/// Unlike an [`Address`], a recipient does not name the message type of the actor's mailbox,
+/// eg. a wrapper message. Messages are converted with [`From`], and responses with
+/// [`Wrap::unwrap_return`].
+///
+/// Like an address, the mailbox is kept open while a recipient to it exists.
+pub struct Recipient<M: Message>(Arc<dyn AnyAddress<M>>);
+
+impl<M: Message> Recipient<M> {
+    /// Creates a new recipient from an address.
+    pub fn new<T: Wrap<M>>(addr: Address<T>) -> Self {
+        Self(Arc::new(addr))
+    }
+
+    /// Sends a message and waits for a response.
+    pub async fn send(&self, msg: M) -> Result<M::Return, Error> {
+        self.0.send(msg).await
+    }
+
+    /// Queues a message, returning once it is queued.
+    pub async fn queue(&self, msg: M) -> Result<(), Error> {
+        self.0.queue(msg).await
+    }
+
+    /// Returns whether the mailbox is connected, ie. whether it is still receiving messages.
+    pub fn is_connected(&self) -> bool {
+        self.0.is_connected()
+    }
+
+    /// Returns a future which resolves once the mailbox has been dropped.
+    ///
+    /// See [`Address::closed`].
+    pub async fn closed(&self) {
+        self.0.closed().await
+    }
+}
+
+impl<M: Message> Clone for Recipient<M> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<M: Message> std::fmt::Debug for Recipient<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recipient")
+            .field("connected", &self.is_connected())
+            .finish()
+    }
+}
+
+impl<T, M> From<Address<T>> for Recipient<M>
+where
+    T: Wrap<M>,
+    M: Message,
+{
+    fn from(addr: Address<T>) -> Self {
+        Self::new(addr)
+    }
+}
+
+/// A recipient keeps the actor running while it is subscribed, use a
+/// [`WeakAddress`](crate::WeakAddress) to be unsubscribed once the actor is no longer used.
+impl<M: Message> Subscriber<M> for Recipient<M> {
+    fn deliver(&self, msg: M) -> BoxFuture<'static, Result<(), Error>> {
+        self.0.queue(msg)
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    collections::HashMap,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context as TaskContext, Poll},
+};
+
+use futures_channel::mpsc;
+use futures_core::{FusedStream, Stream};
+use futures_util::StreamExt;
+
+use crate::{Address, Message, Recipient, WeakAddress, Wrap};
+
+/// The key of an entry in a [`Registry`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Name(Cow<'static, str>),
+    Type(TypeId),
+}
+
+/// A registered address.
+struct Entry {
+    /// The [`WeakAddress`] of the mailbox.
+    addr: Box<dyn Any + Send>,
+    /// Returns whether the mailbox is still connected.
+    is_connected: fn(&(dyn Any + Send)) -> bool,
+}
+
+impl Entry {
+    fn new<T: Message>(addr: WeakAddress<T>) -> Self {
+        Self {
+            addr: Box::new(addr),
+            is_connected: |addr| {
+                addr.downcast_ref::<WeakAddress<T>>()
+                    .is_some_and(WeakAddress::is_connected)
+            },
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        (self.is_connected)(self.addr.as_ref())
+    }
+
+    fn get<T: Message>(&self) -> Option<WeakAddress<T>> {
+        self.addr.downcast_ref::<WeakAddress<T>>().cloned()
+    }
+}
+
+type Watcher = mpsc::UnboundedSender<Box<dyn Any + Send>>;
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<Key, Entry>,
+    watchers: HashMap<Key, Vec<Watcher>>,
+}
+
+impl Inner {
+    /// Removes the entries of mailboxes which have been closed.
+    fn prune(&mut self) {
+        self.entries.retain(|_, entry| entry.is_connected());
+        self.watchers.retain(|_, watchers| {
+            watchers.retain(|watcher| !watcher.is_closed());
+            !watchers.is_empty()
+        });
+    }
+
+    fn get<T: Message>(&mut self, key: &Key) -> Option<WeakAddress<T>> {
+        self.prune();
+        self.entries.get(key).and_then(Entry::get)
+    }
+
+    fn register<T: Message>(&mut self, key: Key, addr: &Address<T>) {
+        self.prune();
+        if let Some(watchers) = self.watchers.get_mut(&key) {
+            watchers.retain(|watcher| watcher.unbounded_send(Box::new(addr.downgrade())).is_ok());
+        }
+        self.entries.insert(key, Entry::new(addr.downgrade()));
+    }
+
+    fn unregister(&mut self, key: &Key) -> bool {
+        self.prune();
+        self.entries.remove(key).is_some()
+    }
+
+    fn watch<T: Message>(&mut self, key: Key) -> Watch<T> {
+        let (sender, receiver): (Watcher, _) = mpsc::unbounded();
+        if let Some(addr) = self.get::<T>(&key) {
+            _ = sender.unbounded_send(Box::new(addr));
+        }
+        self.watchers.entry(key).or_default().push(sender);
+
+        Watch {
+            receiver,
+            _pd: PhantomData,
+        }
+    }
+}
+
+/// A registry of actor addresses, which can be looked up by name or by a marker type.
+///
+/// A registry is a cheap handle, clones of it share the same entries. Only a [`WeakAddress`]
+/// is kept for every entry, so registering an actor does not keep it running. Entries are
+/// removed automatically once their mailbox is closed.
+///
+/// Looking up an entry with a different message type than it was registered with returns
+/// `None`.
+#[derive(Default, Clone)]
+pub struct Registry {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("entries", &self.len())
+            .finish()
+    }
+}
+
+impl Registry {
+    /// Creates a new registry without any entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Registers an address under a name, replacing any address which was previously
+    /// registered under it.
+    ///
+    /// Every [`Watch`] of the name is notified of the new address.
+    pub fn register<T: Message>(&self, name: impl Into<Cow<'static, str>>, addr: &Address<T>) {
+        self.lock().register(Key::Name(name.into()), addr)
+    }
+
+    /// Registers an address under the marker type `K`, replacing any address which was
+    /// previously registered under it.
+    ///
+    /// Every [`Watch`] of the marker type is notified of the new address.
+    pub fn register_type<K: 'static, T: Message>(&self, addr: &Address<T>) {
+        self.lock().register(Key::Type(TypeId::of::<K>()), addr)
+    }
+
+    /// Removes the address registered under a name, returns `true` if there was one.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.lock()
+            .unregister(&Key::Name(Cow::Owned(name.to_string())))
+    }
+
+    /// Removes the address registered under the marker type `K`, returns `true` if there
+    /// was one.
+    pub fn unregister_type<K: 'static>(&self) -> bool {
+        self.lock().unregister(&Key::Type(TypeId::of::<K>()))
+    }
+
+    /// Returns the address registered under a name.
+    pub fn lookup<T: Message>(&self, name: &str) -> Option<WeakAddress<T>> {
+        self.lock().get(&Key::Name(Cow::Owned(name.to_string())))
+    }
+
+    /// Returns the address registered under the marker type `K`.
+    pub fn lookup_type<K: 'static, T: Message>(&self) -> Option<WeakAddress<T>> {
+        self.lock().get(&Key::Type(TypeId::of::<K>()))
+    }
+
+    /// Returns a [`Recipient`] of the address registered under a name.
+    ///
+    /// Returns `None` if there is no such address, or if every address to the mailbox has
+    /// been dropped.
+    pub fn recipient<T, M>(&self, name: &str) -> Option<Recipient<M>>
+    where
+        T: Wrap<M>,
+        M: Message,
+    {
+        self.lookup::<T>(name)?.upgrade().map(Recipient::new)
+    }
+
+    /// Returns a [`Recipient`] of the address registered under the marker type `K`.
+    ///
+    /// Returns `None` if there is no such address, or if every address to the mailbox has
+    /// been dropped.
+    pub fn recipient_type<K, T, M>(&self) -> Option<Recipient<M>>
+    where
+        K: 'static,
+        T: Wrap<M>,
+        M: Message,
+    {
+        self.lookup_type::<K, T>()?.upgrade().map(Recipient::new)
+    }
+
+    /// Returns a stream of the addresses registered under a name.
+    ///
+    /// The stream yields the address which is currently registered, if any, followed by
+    /// every address which is registered afterwards, eg. once the actor is restarted.
+    pub fn watch<T: Message>(&self, name: impl Into<Cow<'static, str>>) -> Watch<T> {
+        self.lock().watch(Key::Name(name.into()))
+    }
+
+    /// Returns a stream of the addresses registered under the marker type `K`, see
+    /// [`Registry::watch`].
+    pub fn watch_type<K: 'static, T: Message>(&self) -> Watch<T> {
+        self.lock().watch(Key::Type(TypeId::of::<K>()))
+    }
+
+    /// Returns the number of registered addresses.
+    pub fn len(&self) -> usize {
+        let mut inner = self.lock();
+        inner.prune();
+        inner.entries.len()
+    }
+
+    /// Returns `true` if there are no registered addresses.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A stream of the addresses registered under a key of a [`Registry`], see
+/// [`Registry::watch`].
+///
+/// Addresses registered with a different message type are skipped. The stream never ends
+/// while the registry exists.
+#[must_use = "streams do nothing unless polled"]
+pub struct Watch<T: Message> {
+    receiver: mpsc::UnboundedReceiver<Box<dyn Any + Send>>,
+    _pd: PhantomData<fn() -> T>,
+}
+
+impl<T: Message> std::fmt::Debug for Watch<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watch").finish_non_exhaustive()
+    }
+}
+
+impl<T: Message> Stream for Watch<T> {
+    type Item = WeakAddress<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match futures_core::ready!(self.receiver.poll_next_unpin(cx)) {
+                Some(addr) => {
+                    if let Ok(addr) = addr.downcast::<WeakAddress<T>>() {
+                        return Poll::Ready(Some(*addr));
+                    }
+                }
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<T: Message> FusedStream for Watch<T> {
+    fn is_terminated(&self) -> bool {
+        self.receiver.is_terminated()
+    }
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use crate::{
+    metrics::MetricsSink, run_concurrent_with_context, run_many_with_context, run_with_context,
+    timer::Timer, Actor, Context, Dispatch, Layer, LayerStack, Mailbox, MailboxSet, Message,
+    RunnerLayers,
+};
+
+/// A configurable run loop for an actor.
+///
+/// Unlike [`run`](crate::run), a runner gives the actor's [`Context`] access to its own
+/// mailbox, see [`Context::address`]. This enables features such as scheduling messages
+/// to itself.
+///
+/// A runner without layers can run an actor with a mailbox of any type of message, once a
+/// layer is added the runner is a `Runner<LayerStack<T>>` which only runs mailboxes of
+/// messages of type `T`, see [`Runner::with_layer`].
+#[derive(Default, Clone)]
+pub struct Runner<L = ()> {
+    timer: Option<Arc<dyn Timer>>,
+    metrics: Option<Arc<dyn MetricsSink>>,
+    layers: L,
+    catch_panics: bool,
+    skip_canceled: bool,
+    max_batch_size: Option<usize>,
+}
+
+impl Runner {
+    /// Creates a new runner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer which wraps the dispatch of every message the actor handles, see
+    /// [`Layer`].
+    ///
+    /// Layers added first are outermost, so they see a message before the layers added after
+    /// them. The runner can then only run an actor with a mailbox of messages of type `T`.
+    pub fn with_layer<T: Message>(self, layer: impl Layer<T>) -> Runner<LayerStack<T>> {
+        Runner {
+            timer: self.timer,
+            metrics: self.metrics,
+            layers: LayerStack::new(layer),
+            catch_panics: self.catch_panics,
+            skip_canceled: self.skip_canceled,
+            max_batch_size: self.max_batch_size,
+        }
+    }
+
+    /// Runs an actor with several mailboxes until it receives a stop signal, an error occurs,
+    /// or every mailbox is closed.
+    ///
+    /// See [`run_many`](crate::run_many). The runner can not have layers, as the mailboxes
+    /// receive different types of messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor to run.
+    /// * `mailboxes` - The mailboxes which will be used to receive messages.
+    pub async fn run_many<A>(
+        &self,
+        actor: &mut A,
+        mailboxes: &mut MailboxSet<A>,
+    ) -> Result<A::Stop, A::Error>
+    where
+        A: Actor,
+    {
+        let mut ctx = self.base_context();
+        mailboxes.add_to(&mut ctx);
+
+        run_many_with_context(actor, mailboxes, ctx).await
+    }
+}
+
+impl<T: Message> Runner<LayerStack<T>> {
+    /// Adds a layer which wraps the dispatch of every message the actor handles, see
+    /// [`Layer`].
+    ///
+    /// Layers added first are outermost, so they see a message before the layers added after
+    /// them.
+    pub fn with_layer(mut self, layer: impl Layer<T>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+}
+
+impl<L> Runner<L> {
+    /// Sets the timer used to schedule messages.
+    pub fn with_timer(mut self, timer: impl Timer) -> Self {
+        self.timer = Some(Arc::new(timer));
+        self
+    }
+
+    /// Sets the sink which records metrics of the actor, disabled by default.
+    ///
+    /// See [`metrics`](crate::metrics).
+    pub fn with_metrics(mut self, metrics: impl MetricsSink) -> Self {
+        self.metrics = Some(Arc::new(metrics));
+        self
+    }
+
+    /// Sets whether panics in handlers are caught, disabled by default.
+    ///
+    /// When enabled, the sender of a message which panicked receives
+    /// [`Error::Panicked`](crate::Error::Panicked) and the actor decides whether to keep
+    /// running, see [`Actor::panicked`].
+    pub fn with_catch_panics(mut self, catch_panics: bool) -> Self {
+        self.catch_panics = catch_panics;
+        self
+    }
+
+    /// Sets whether messages are skipped if their sender is no longer waiting for a
+    /// response, disabled by default.
+    ///
+    /// Messages which do not expect a response are always handled.
+    pub fn with_skip_canceled(mut self, skip_canceled: bool) -> Self {
+        self.skip_canceled = skip_canceled;
+        self
+    }
+
+    /// Sets the maximum number of messages which are handled in a single batch, 64 by
+    /// default.
+    ///
+    /// See [`BatchHandler`](crate::BatchHandler).
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = Some(max_batch_size);
+        self
+    }
+
+    /// Runs an actor until it receives a stop signal or an error occurs.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor to run.
+    /// * `mailbox` - The mailbox which will be used to receive messages.
+    pub async fn run<A, T>(
+        &self,
+        actor: &mut A,
+        mailbox: &mut Mailbox<T>,
+    ) -> Result<A::Stop, A::Error>
+    where
+        A: Actor,
+        T: Dispatch<A>,
+        L: RunnerLayers<T>,
+    {
+        run_with_context(actor, mailbox, self.context(mailbox)).await
+    }
+
+    /// Runs an actor until it receives a stop signal or an error occurs, handling concurrent
+    /// messages concurrently.
+    ///
+    /// See [`run_concurrent`](crate::run_concurrent).
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor to run.
+    /// * `mailbox` - The mailbox which will be used to receive messages.
+    /// * `limit` - The maximum number of messages to handle concurrently.
+    pub async fn run_concurrent<A, T>(
+        &self,
+        actor: &mut A,
+        mailbox: &mut Mailbox<T>,
+        limit: usize,
+    ) -> Result<A::Stop, A::Error>
+    where
+        A: Actor + Sync,
+        T: Dispatch<A>,
+        L: RunnerLayers<T>,
+    {
+        run_concurrent_with_context(actor, mailbox, self.context(mailbox), limit).await
+    }
+
+    fn context<A, T>(&self, mailbox: &Mailbox<T>) -> Context<A>
+    where
+        A: Actor,
+        T: Dispatch<A>,
+        L: RunnerLayers<T>,
+    {
+        let mut ctx = self.base_context();
+        if let Some(layers) = self.layers.layers() {
+            ctx.set_layers::<T>(layers);
+        }
+        ctx.add_mailbox(mailbox);
+
+        ctx
+    }
+
+    /// Returns a context with the configuration of the runner, without any mailboxes.
+    fn base_context<A: Actor>(&self) -> Context<A> {
+        let mut ctx = Context::default();
+        ctx.set_catch_panics(self.catch_panics);
+        ctx.set_skip_canceled(self.skip_canceled);
+        if let Some(max_batch_size) = self.max_batch_size {
+            ctx.set_max_batch_size(max_batch_size);
+        }
+        if let Some(timer) = &self.timer {
+            ctx.set_timer(timer.clone());
+        }
+        if let Some(metrics) = &self.metrics {
+            ctx.set_metrics(metrics.clone());
+        }
+
+        ctx
+    }
+}
@@ -0,0 +1,194 @@
+use std::task::{Context as TaskContext, Poll};
+
+use futures_util::{future::poll_fn, FutureExt, StreamExt};
+
+use crate::{
+    attach::{Mail, StreamEvent},
+    Actor, Context, Dispatch, Error, Mailbox,
+};
+
+/// A policy which determines how a [`MailboxSet`] chooses the mailbox to receive from next.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectPolicy {
+    /// Always receive from the first mailbox, in the order they were added, with a queued
+    /// message.
+    ///
+    /// Later mailboxes may starve if earlier mailboxes are never empty.
+    Biased,
+    /// Give each mailbox a turn in the order they were added, skipping those without a
+    /// queued message.
+    #[default]
+    RoundRobin,
+}
+
+/// A set of mailboxes, which may receive different types of messages, that an actor receives
+/// messages from, see [`run_many`](crate::run_many).
+///
+/// A mailbox is removed from the set once it is closed, and the set is closed once every
+/// mailbox has been removed.
+pub struct MailboxSet<A: Actor> {
+    mailboxes: Vec<Box<dyn Source<A>>>,
+    policy: SelectPolicy,
+    /// The mailbox which is given the next turn.
+    cursor: usize,
+}
+
+impl<A: Actor> MailboxSet<A> {
+    /// Creates a new, empty, set of mailboxes which are selected round-robin.
+    pub fn new() -> Self {
+        Self::with_policy(SelectPolicy::default())
+    }
+
+    /// Creates a new, empty, set of mailboxes which are selected with the given policy.
+    pub fn with_policy(policy: SelectPolicy) -> Self {
+        Self {
+            mailboxes: Vec::new(),
+            policy,
+            cursor: 0,
+        }
+    }
+
+    /// Adds a mailbox to the set.
+    pub fn push<T: Dispatch<A>>(&mut self, mailbox: Mailbox<T>) {
+        self.mailboxes.push(Box::new(mailbox));
+    }
+
+    /// Adds a mailbox to the set, returning the set.
+    pub fn with<T: Dispatch<A>>(mut self, mailbox: Mailbox<T>) -> Self {
+        self.push(mailbox);
+        self
+    }
+
+    /// Returns the number of open mailboxes in the set.
+    pub fn len(&self) -> usize {
+        self.mailboxes.len()
+    }
+
+    /// Returns `true` if there are no open mailboxes in the set.
+    pub fn is_empty(&self) -> bool {
+        self.mailboxes.is_empty()
+    }
+
+    /// Closes every mailbox in the set, preventing any new messages from being sent.
+    ///
+    /// See [`Mailbox::close`].
+    pub fn close(&mut self) {
+        for mailbox in &mut self.mailboxes {
+            mailbox.close();
+        }
+    }
+
+    /// Adds every mailbox in the set to the context, see [`Context::add_mailbox`].
+    pub(crate) fn add_to(&self, ctx: &mut Context<A>) {
+        for mailbox in &self.mailboxes {
+            mailbox.add_to(ctx);
+        }
+    }
+
+    /// Rejects every queued message with [`Error::Stopped`].
+    pub(crate) fn reject(&mut self) {
+        for mailbox in &mut self.mailboxes {
+            mailbox.reject();
+        }
+    }
+
+    /// Receives the next queued message without waiting, if there is one.
+    pub(crate) fn try_next(&mut self) -> Option<Box<dyn StreamEvent<A>>> {
+        poll_fn(|cx| self.poll_next(cx)).now_or_never().flatten()
+    }
+
+    /// Polls the mailboxes for the next message according to the policy.
+    ///
+    /// Returns `None` once every mailbox is closed.
+    pub(crate) fn poll_next(
+        &mut self,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<Box<dyn StreamEvent<A>>>> {
+        let start = match self.policy {
+            SelectPolicy::Biased => 0,
+            SelectPolicy::RoundRobin => self.cursor,
+        };
+
+        let mut turns = self.mailboxes.len();
+        let mut i = start;
+        while turns > 0 {
+            turns -= 1;
+            if i >= self.mailboxes.len() {
+                i = 0;
+            }
+
+            match self.mailboxes[i].poll_next(cx) {
+                Poll::Ready(Some(event)) => {
+                    self.cursor = i + 1;
+                    return Poll::Ready(Some(event));
+                }
+                Poll::Ready(None) => {
+                    // The mailbox is closed, the next mailbox takes its place.
+                    self.mailboxes.remove(i);
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        if self.mailboxes.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<A: Actor> Default for MailboxSet<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Actor> std::fmt::Debug for MailboxSet<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MailboxSet")
+            .field("len", &self.mailboxes.len())
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A mailbox which the messages of an actor are received from.
+trait Source<A: Actor>: Send {
+    /// Polls the mailbox for the next message.
+    fn poll_next(&mut self, cx: &mut TaskContext<'_>) -> Poll<Option<Box<dyn StreamEvent<A>>>>;
+
+    /// Closes the mailbox, see [`Mailbox::close`].
+    fn close(&mut self);
+
+    /// Rejects every queued message with [`Error::Stopped`].
+    fn reject(&mut self);
+
+    /// Adds the mailbox to the context, see [`Context::add_mailbox`].
+    fn add_to(&self, ctx: &mut Context<A>);
+}
+
+impl<A, T> Source<A> for Mailbox<T>
+where
+    A: Actor,
+    T: Dispatch<A>,
+{
+    fn poll_next(&mut self, cx: &mut TaskContext<'_>) -> Poll<Option<Box<dyn StreamEvent<A>>>> {
+        self.poll_next_unpin(cx)
+            .map(|env| env.map(|env| Box::new(Mail(env)) as Box<dyn StreamEvent<A>>))
+    }
+
+    fn close(&mut self) {
+        Mailbox::close(self)
+    }
+
+    fn reject(&mut self) {
+        while let Some(Some(env)) = self.next().now_or_never() {
+            env.reject(Error::Stopped);
+        }
+    }
+
+    fn add_to(&self, ctx: &mut Context<A>) {
+        ctx.add_mailbox(self);
+    }
+}
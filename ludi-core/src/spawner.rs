@@ -0,0 +1,187 @@
+//! Executor-agnostic spawning of actors.
+//!
+//! ludi is not coupled to a runtime, so spawning an actor requires a [`Spawner`], see
+//! [`spawn`](crate::spawn).
+//!
+//! Adapters for common runtimes are available behind cargo features:
+//!
+//! * `tokio` - [`TokioSpawner`]
+//! * `async-std` - [`AsyncStdSpawner`]
+//! * `smol` - [`SmolSpawner`]
+//! * `thread-pool` - `futures::executor::ThreadPool` implements [`Spawner`].
+
+use std::{any::Any, fmt::Display, future::Future, panic::AssertUnwindSafe, pin::Pin};
+
+use futures_channel::oneshot;
+use futures_util::{
+    future::{AbortHandle, Abortable},
+    FutureExt,
+};
+
+use crate::{Actor, Address, Dispatch, Mailbox, Message, Runner, RunnerLayers, WeakAddress};
+
+/// A future which runs a spawned actor.
+pub type Task = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A spawner of tasks.
+pub trait Spawner {
+    /// Spawns a task to run in the background.
+    fn spawn(&self, task: Task);
+}
+
+type JoinResult<A> =
+    Result<Result<<A as Actor>::Stop, <A as Actor>::Error>, Box<dyn Any + Send + 'static>>;
+
+/// The reason a spawned actor did not complete, see [`ActorHandle::join`].
+#[derive(Debug)]
+pub enum JoinError {
+    /// The actor panicked, with the panic's payload.
+    Panicked(Box<dyn Any + Send + 'static>),
+    /// The spawner dropped the actor's task before it completed.
+    Aborted,
+}
+
+impl Display for JoinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JoinError::Panicked(_) => write!(f, "actor panicked"),
+            JoinError::Aborted => write!(f, "actor task was dropped before it completed"),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+/// A handle to a spawned actor.
+///
+/// Dropping the handle detaches the actor, it keeps running until it stops.
+pub struct ActorHandle<A: Actor, T: Message> {
+    addr: WeakAddress<T>,
+    abort: AbortHandle,
+    result: oneshot::Receiver<JoinResult<A>>,
+}
+
+impl<A: Actor, T: Message> std::fmt::Debug for ActorHandle<A, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorHandle")
+            .field("aborted", &self.abort.is_aborted())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Actor, T: Message> ActorHandle<A, T> {
+    /// Returns an address to the actor's mailbox.
+    ///
+    /// The handle only holds a weak reference to the mailbox, so it does not keep the actor
+    /// running. Returns `None` if every other address to the mailbox has been dropped.
+    pub fn address(&self) -> Option<Address<T>> {
+        self.addr.upgrade()
+    }
+
+    /// Waits for the actor to stop, returning its stop value or error.
+    ///
+    /// Returns a [`JoinError`] if the actor panicked, or if the spawner dropped the actor's
+    /// task before it completed.
+    pub async fn join(self) -> Result<Result<A::Stop, A::Error>, JoinError> {
+        match self.result.await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(payload)) => Err(JoinError::Panicked(payload)),
+            Err(_) => Err(JoinError::Aborted),
+        }
+    }
+
+    /// Aborts the actor.
+    ///
+    /// The actor is dropped without being stopped, and any messages remaining in its mailbox
+    /// are rejected with [`Error::Stopped`](crate::Error::Stopped).
+    pub fn abort(self) {
+        self.abort.abort();
+    }
+}
+
+impl<L> Runner<L> {
+    /// Spawns an actor, returning a handle to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - The actor to run.
+    /// * `mailbox` - The mailbox which will be used to receive messages.
+    /// * `spawner` - The spawner which runs the actor.
+    pub fn spawn<A, T>(
+        &self,
+        mut actor: A,
+        mut mailbox: Mailbox<T>,
+        spawner: &impl Spawner,
+    ) -> ActorHandle<A, T>
+    where
+        A: Actor + 'static,
+        A::Stop: Send + 'static,
+        T: Dispatch<A>,
+        L: RunnerLayers<T>,
+    {
+        let addr = mailbox.weak_address();
+        let (result_tx, result_rx) = oneshot::channel();
+        let (abort, registration) = AbortHandle::new_pair();
+
+        let runner = self.clone();
+        let fut = async move {
+            let result = AssertUnwindSafe(runner.run(&mut actor, &mut mailbox))
+                .catch_unwind()
+                .await;
+            let _ = result_tx.send(result);
+        };
+
+        spawner.spawn(Box::pin(Abortable::new(fut, registration).map(|_| ())));
+
+        ActorHandle {
+            addr,
+            abort,
+            result: result_rx,
+        }
+    }
+}
+
+/// A [`Spawner`] backed by `tokio`.
+///
+/// Tasks are spawned onto the current runtime.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioSpawner;
+
+#[cfg(feature = "tokio")]
+impl Spawner for TokioSpawner {
+    fn spawn(&self, task: Task) {
+        tokio::spawn(task);
+    }
+}
+
+/// A [`Spawner`] backed by `async-std`.
+#[cfg(feature = "async-std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsyncStdSpawner;
+
+#[cfg(feature = "async-std")]
+impl Spawner for AsyncStdSpawner {
+    fn spawn(&self, task: Task) {
+        async_std::task::spawn(task);
+    }
+}
+
+/// A [`Spawner`] backed by `smol`.
+#[cfg(feature = "smol")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SmolSpawner;
+
+#[cfg(feature = "smol")]
+impl Spawner for SmolSpawner {
+    fn spawn(&self, task: Task) {
+        smol::spawn(task).detach();
+    }
+}
+
+#[cfg(feature = "thread-pool")]
+impl Spawner for futures_executor::ThreadPool {
+    fn spawn(&self, task: Task) {
+        self.spawn_ok(task);
+    }
+}
@@ -0,0 +1,127 @@
+use std::{
+    pin::{pin, Pin},
+    task::{Context as TaskContext, Poll},
+};
+
+use futures_channel::mpsc;
+use futures_core::{FusedStream, Future, Stream};
+use futures_util::{SinkExt, StreamExt};
+
+use crate::{Actor, Context, Error, Message};
+
+/// The number of items which are buffered by a [`ResponseStream`] before the handler has to
+/// wait for the sender to receive them.
+const STREAM_CAPACITY: usize = 16;
+
+/// A message which is responded to with a stream of items, see [`StreamingHandler`].
+pub trait StreamMessage: Message<Return = ResponseStream<<Self as StreamMessage>::Item>> {
+    /// The type of the items in the response.
+    type Item: Send + 'static;
+}
+
+/// An actor that can handle a message by streaming items back to the caller.
+pub trait StreamingHandler<T: StreamMessage>: Actor {
+    /// Handle a message, sending items to the caller.
+    ///
+    /// The response stream ends once `items` is dropped, eg. once this method returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to handle.
+    /// * `items` - A sender for the items of the response.
+    /// * `ctx` - The actor's execution context.
+    fn handle_stream(
+        &mut self,
+        msg: T,
+        items: StreamSender<T::Item>,
+        ctx: &mut Context<Self>,
+    ) -> impl Future<Output = ()> + Send;
+
+    /// Handle a message, returning a stream to the caller before the items are sent.
+    ///
+    /// By default, this method returns the stream and then calls
+    /// [`StreamingHandler::handle_stream`].
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to handle.
+    /// * `ctx` - The actor's execution context.
+    /// * `ret` - A channel which returns the stream to the caller.
+    ///
+    /// # Buffering
+    ///
+    /// The stream buffers a limited number of items, after which the handler waits for the
+    /// caller to receive them. The stream is returned to the caller as soon as `ret` is called,
+    /// even if the actor is run with a [`Layer`](crate::Layer).
+    fn process_stream<R: FnOnce(ResponseStream<T::Item>) + Send>(
+        &mut self,
+        msg: T,
+        ctx: &mut Context<Self>,
+        ret: R,
+    ) -> impl Future<Output = ()> + Send {
+        async move {
+            let (sender, receiver) = mpsc::channel(STREAM_CAPACITY);
+            ret(ResponseStream(receiver));
+            self.handle_stream(msg, StreamSender(sender), ctx).await
+        }
+    }
+}
+
+/// A sender for the items of a [`ResponseStream`].
+#[derive(Debug)]
+pub struct StreamSender<T>(mpsc::Sender<T>);
+
+impl<T> StreamSender<T> {
+    /// Sends an item to the caller, waiting if the stream's buffer is full.
+    ///
+    /// Returns [`Error::Disconnected`] if the caller dropped the stream.
+    pub async fn send(&mut self, item: T) -> Result<(), Error> {
+        self.0.send(item).await.map_err(|_| Error::Disconnected)
+    }
+
+    /// Sends every item of a stream to the caller.
+    ///
+    /// Returns [`Error::Disconnected`] if the caller dropped the stream, in which case the
+    /// remaining items are not polled.
+    pub async fn forward<S>(mut self, stream: S) -> Result<(), Error>
+    where
+        S: Stream<Item = T>,
+    {
+        let mut stream = pin!(stream);
+        while let Some(item) = stream.next().await {
+            self.send(item).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the caller dropped the stream.
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+/// A stream of items sent in response to a [`StreamMessage`].
+///
+/// The stream ends once the handler is done sending items.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct ResponseStream<T>(mpsc::Receiver<T>);
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_next_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> FusedStream for ResponseStream<T> {
+    fn is_terminated(&self) -> bool {
+        self.0.is_terminated()
+    }
+}
@@ -0,0 +1,359 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use futures_core::{future::BoxFuture, Stream};
+use futures_util::{
+    future::{AbortHandle, Abortable},
+    stream::FuturesUnordered,
+    FutureExt, StreamExt,
+};
+
+use crate::{Actor, Dispatch, Envelope, Mailbox, Runner, RunnerLayers};
+
+/// A strategy which determines which children are restarted when one of them fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Only the failed child is restarted.
+    OneForOne,
+    /// All children are restarted.
+    OneForAll,
+    /// The failed child and every child added after it are restarted.
+    RestForOne,
+}
+
+/// The maximum number of restarts a supervisor will perform within a window of time.
+///
+/// If a supervisor needs to restart more than `max_restarts` children within `window` it
+/// gives up and returns [`RestartIntensityExceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartIntensity {
+    max_restarts: usize,
+    window: Duration,
+}
+
+impl RestartIntensity {
+    /// Creates a new restart intensity.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_restarts` - The maximum number of restarts allowed within the window.
+    /// * `window` - The window of time.
+    pub fn new(max_restarts: usize, window: Duration) -> Self {
+        Self {
+            max_restarts,
+            window,
+        }
+    }
+}
+
+impl Default for RestartIntensity {
+    fn default() -> Self {
+        Self::new(3, Duration::from_secs(5))
+    }
+}
+
+/// Error returned when a supervisor exceeds its [`RestartIntensity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartIntensityExceeded;
+
+impl Display for RestartIntensityExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "restart intensity exceeded")
+    }
+}
+
+impl std::error::Error for RestartIntensityExceeded {}
+
+/// A supervisor which runs a group of actors and restarts them when they fail.
+///
+/// Each child is made up of a factory, which is used to build a fresh actor on every
+/// (re)start, and a mailbox. The mailbox stays attached to the child across restarts, so
+/// any [`Address`](crate::Address) or controller pointing to it keeps working.
+///
+/// A child fails when [`run`](crate::run) returns an error. A child which stops normally,
+/// either because it received a stop signal or because its mailbox was closed, is not
+/// restarted. Children added with [`Supervisor::add_with_runner`] are run by a [`Runner`]
+/// instead, and decide whether to restart from the error they failed with.
+///
+/// The supervisor itself is a future, and like [`run`](crate::run) it can be driven by any
+/// executor.
+pub struct Supervisor {
+    strategy: Strategy,
+    intensity: RestartIntensity,
+    children: Vec<Box<dyn Child>>,
+}
+
+impl Supervisor {
+    /// Creates a new supervisor with the given restart strategy.
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            intensity: RestartIntensity::default(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets the restart intensity of the supervisor.
+    pub fn with_intensity(mut self, intensity: RestartIntensity) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Adds a child to the supervisor.
+    ///
+    /// Children are started in the order they are added.
+    ///
+    /// # Arguments
+    ///
+    /// * `factory` - A closure which builds the actor, called every time the child is (re)started.
+    /// * `mailbox` - The mailbox which will be used to receive messages.
+    pub fn add<A, F, M, T>(&mut self, factory: F, mailbox: M) -> &mut Self
+    where
+        A: Actor + 'static,
+        F: FnMut() -> A + Send + 'static,
+        M: Stream<Item = Envelope<T>> + Send + Unpin + 'static,
+        T: Dispatch<A>,
+    {
+        self.children.push(Box::new(ChildSpec {
+            factory,
+            mailbox,
+            runner: PlainRunner,
+            restart: |_| true,
+            _pd: PhantomData,
+        }));
+        self
+    }
+
+    /// Adds a child which is run by a [`Runner`], see [`Supervisor::add`].
+    ///
+    /// The runner gives the child access to its own address, see
+    /// [`Context::address`](crate::Context::address), and its options apply every time the
+    /// child is (re)started.
+    ///
+    /// # Arguments
+    ///
+    /// * `runner` - The runner which is used to run the actor.
+    /// * `factory` - A closure which builds the actor, called every time the child is (re)started.
+    /// * `mailbox` - The mailbox which will be used to receive messages.
+    /// * `restart` - A closure which is called with the error every time the child fails, and
+    ///   returns whether the child should be restarted. A child which is not restarted is
+    ///   treated as stopped.
+    pub fn add_with_runner<A, F, T, R, L>(
+        &mut self,
+        runner: Runner<L>,
+        factory: F,
+        mailbox: Mailbox<T>,
+        restart: R,
+    ) -> &mut Self
+    where
+        A: Actor + 'static,
+        F: FnMut() -> A + Send + 'static,
+        T: Dispatch<A>,
+        R: FnMut(A::Error) -> bool + Send + 'static,
+        L: RunnerLayers<T>,
+    {
+        self.children.push(Box::new(ChildSpec {
+            factory,
+            mailbox,
+            runner,
+            restart,
+            _pd: PhantomData,
+        }));
+        self
+    }
+
+    /// Runs the supervisor until all of its children have stopped.
+    ///
+    /// Returns an error if the restart intensity was exceeded, in which case all children
+    /// which are still running are stopped.
+    pub async fn run(self) -> Result<(), RestartIntensityExceeded> {
+        let Self {
+            strategy,
+            intensity,
+            children,
+        } = self;
+
+        let count = children.len();
+        let mut handles: Vec<Option<AbortHandle>> = (0..count).map(|_| None).collect();
+        let mut idle: Vec<Option<Box<dyn Child>>> = (0..count).map(|_| None).collect();
+        let mut running = FuturesUnordered::new();
+        let mut restarts = VecDeque::new();
+        // Children which are waiting to be restarted after being stopped by the supervisor.
+        let mut restarting = Vec::new();
+
+        for (idx, child) in children.into_iter().enumerate() {
+            let (handle, fut) = start(idx, child);
+            handles[idx] = Some(handle);
+            running.push(fut);
+        }
+
+        while let Some((idx, child, exit)) = running.next().await {
+            handles[idx] = None;
+
+            if exit == Exit::Failed {
+                let now = Instant::now();
+                while restarts
+                    .front()
+                    .is_some_and(|at| now.duration_since(*at) > intensity.window)
+                {
+                    restarts.pop_front();
+                }
+
+                if restarts.len() >= intensity.max_restarts {
+                    return Err(RestartIntensityExceeded);
+                }
+                restarts.push_back(now);
+
+                if !restarting.contains(&idx) {
+                    restarting.push(idx);
+                }
+
+                let siblings: Vec<usize> = match strategy {
+                    Strategy::OneForOne => Vec::new(),
+                    Strategy::OneForAll => (0..count).filter(|i| *i != idx).collect(),
+                    Strategy::RestForOne => (idx + 1..count).collect(),
+                };
+
+                for sibling in siblings {
+                    if let Some(handle) = handles[sibling].as_ref() {
+                        handle.abort();
+                        if !restarting.contains(&sibling) {
+                            restarting.push(sibling);
+                        }
+                    }
+                }
+            }
+
+            match exit {
+                Exit::Stopped => restarting.retain(|i| *i != idx),
+                Exit::Failed | Exit::Aborted => idle[idx] = Some(child),
+            }
+
+            // Wait until every child which is being restarted has returned, then restart
+            // them in the order they were added.
+            if restarting.iter().all(|idx| idle[*idx].is_some()) {
+                restarting.sort_unstable();
+                for idx in restarting.drain(..) {
+                    let child = idle[idx].take().expect("child is idle");
+                    let (handle, fut) = start(idx, child);
+                    handles[idx] = Some(handle);
+                    running.push(fut);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    /// The actor stopped normally.
+    Stopped,
+    /// The actor returned an error.
+    Failed,
+    /// The actor was stopped by the supervisor.
+    Aborted,
+}
+
+type ChildFuture = BoxFuture<'static, (usize, Box<dyn Child>, Exit)>;
+
+fn start(idx: usize, mut child: Box<dyn Child>) -> (AbortHandle, ChildFuture) {
+    let (handle, registration) = AbortHandle::new_pair();
+    let fut = async move {
+        let exit = Abortable::new(child.run(), registration)
+            .await
+            .unwrap_or(Exit::Aborted);
+
+        (idx, child, exit)
+    }
+    .boxed();
+
+    (handle, fut)
+}
+
+trait Child: Send {
+    /// Builds a new actor and runs it until it stops.
+    fn run(&mut self) -> BoxFuture<'_, Exit>;
+}
+
+/// Runs a child actor with its mailbox.
+trait ChildRunner<A: Actor, M>: Send + 'static {
+    fn run<'a>(
+        &'a self,
+        actor: &'a mut A,
+        mailbox: &'a mut M,
+    ) -> BoxFuture<'a, Result<A::Stop, A::Error>>;
+}
+
+/// Runs a child with [`run`](crate::run).
+struct PlainRunner;
+
+impl<A, M, T> ChildRunner<A, M> for PlainRunner
+where
+    A: Actor,
+    M: Stream<Item = Envelope<T>> + Send + Unpin + 'static,
+    T: Dispatch<A>,
+{
+    fn run<'a>(
+        &'a self,
+        actor: &'a mut A,
+        mailbox: &'a mut M,
+    ) -> BoxFuture<'a, Result<A::Stop, A::Error>> {
+        crate::run(actor, mailbox).boxed()
+    }
+}
+
+impl<A, T, L> ChildRunner<A, Mailbox<T>> for Runner<L>
+where
+    A: Actor,
+    T: Dispatch<A>,
+    L: RunnerLayers<T>,
+{
+    fn run<'a>(
+        &'a self,
+        actor: &'a mut A,
+        mailbox: &'a mut Mailbox<T>,
+    ) -> BoxFuture<'a, Result<A::Stop, A::Error>> {
+        Runner::run(self, actor, mailbox).boxed()
+    }
+}
+
+struct ChildSpec<A, F, M, R, E> {
+    factory: F,
+    mailbox: M,
+    runner: R,
+    /// Decides whether the child is restarted after failing.
+    restart: E,
+    _pd: PhantomData<fn() -> A>,
+}
+
+impl<A, F, M, R, E> Child for ChildSpec<A, F, M, R, E>
+where
+    A: Actor + 'static,
+    F: FnMut() -> A + Send + 'static,
+    M: Send + 'static,
+    R: ChildRunner<A, M>,
+    E: FnMut(A::Error) -> bool + Send + 'static,
+{
+    fn run(&mut self) -> BoxFuture<'_, Exit> {
+        let mut actor = (self.factory)();
+        async move {
+            match self.runner.run(&mut actor, &mut self.mailbox).await {
+                Ok(_) => Exit::Stopped,
+                Err(err) => {
+                    if (self.restart)(err) {
+                        Exit::Failed
+                    } else {
+                        Exit::Stopped
+                    }
+                }
+            }
+        }
+        .boxed()
+    }
+}
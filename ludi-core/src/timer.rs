@@ -0,0 +1,125 @@
+//! Executor-agnostic timers.
+//!
+//! ludi is not coupled to a runtime, so scheduling messages requires a [`Timer`] to be
+//! provided when running an actor, see [`Runner::with_timer`](crate::Runner::with_timer). If
+//! the runner does not have a timer, the default timer is used.
+//!
+//! Adapters for common runtimes are available behind cargo features:
+//!
+//! * `tokio` - [`TokioTimer`]
+//! * `async-std` - [`AsyncStdTimer`]
+//! * `futures-timer` - [`FuturesTimer`], which works with any executor.
+//!
+//! Timeouts, eg. [`MessageFuture::timeout`](crate::futures::MessageFuture::timeout), use the
+//! timer of the runner which receives the message, or otherwise the default timer which can be
+//! set with [`set_default_timer`]. If it is not set, the adapter of an enabled feature is used.
+//! The `tokio` adapter is only used within a `tokio` runtime, as it can not sleep outside one.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use futures_util::future::AbortHandle;
+
+/// A future which completes once a duration has elapsed.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A timer.
+pub trait Timer: Send + Sync + 'static {
+    /// Returns a future which completes once `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+static DEFAULT_TIMER: OnceLock<Arc<dyn Timer>> = OnceLock::new();
+
+/// Sets the default timer, returns `false` if it has already been set.
+pub fn set_default_timer(timer: impl Timer) -> bool {
+    DEFAULT_TIMER.set(Arc::new(timer)).is_ok()
+}
+
+/// Returns the default timer, see [`set_default_timer`].
+///
+/// If the default timer has not been set, the adapter of an enabled feature is returned. The
+/// `tokio` adapter is only returned when called within a `tokio` runtime.
+pub fn default_timer() -> Option<Arc<dyn Timer>> {
+    if let Some(timer) = DEFAULT_TIMER.get() {
+        return Some(timer.clone());
+    }
+
+    #[cfg(feature = "tokio")]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return Some(Arc::new(TokioTimer));
+    }
+
+    #[cfg(feature = "async-std")]
+    return Some(Arc::new(AsyncStdTimer));
+
+    #[cfg(all(not(feature = "async-std"), feature = "futures-timer"))]
+    return Some(Arc::new(FuturesTimer));
+
+    #[allow(unreachable_code)]
+    None
+}
+
+/// A handle to a scheduled message.
+///
+/// Dropping the handle does not cancel the message, see [`TimerHandle::cancel`].
+#[derive(Debug, Clone)]
+pub struct TimerHandle(AbortHandle);
+
+impl TimerHandle {
+    pub(crate) fn new(handle: AbortHandle) -> Self {
+        Self(handle)
+    }
+
+    /// Cancels the scheduled message.
+    ///
+    /// Has no effect if the message has already been sent.
+    pub fn cancel(&self) {
+        self.0.abort();
+    }
+
+    /// Returns `true` if the scheduled message has been canceled.
+    pub fn is_canceled(&self) -> bool {
+        self.0.is_aborted()
+    }
+}
+
+/// A [`Timer`] backed by `tokio`.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// A [`Timer`] backed by `async-std`.
+#[cfg(feature = "async-std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsyncStdTimer;
+
+#[cfg(feature = "async-std")]
+impl Timer for AsyncStdTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// A [`Timer`] backed by `futures-timer`.
+#[cfg(feature = "futures-timer")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FuturesTimer;
+
+#[cfg(feature = "futures-timer")]
+impl Timer for FuturesTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(futures_timer::Delay::new(duration))
+    }
+}
@@ -17,3 +17,24 @@ where
     U: ludi::Message,
 {
 }
+
+pub fn assert_concurrent_handler<T, U>()
+where
+    T: ludi::ConcurrentHandler<U>,
+    U: ludi::Message,
+{
+}
+
+pub fn assert_streaming_handler<T, U>()
+where
+    T: ludi::StreamingHandler<U>,
+    U: ludi::StreamMessage,
+{
+}
+
+pub fn assert_batch_handler<T, U>()
+where
+    T: ludi::BatchHandler<U>,
+    U: ludi::Message,
+{
+}
@@ -0,0 +1,64 @@
+#![allow(dead_code)]
+
+use ludi_macros_test::*;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u64, batch)]
+struct Insert(u32);
+
+#[derive(ludi::Message)]
+#[ludi(batch)]
+struct Remove(u32);
+
+#[derive(ludi::Wrap)]
+enum StoreMsg {
+    Insert(Insert),
+    Remove(Remove),
+}
+
+#[derive(Default)]
+pub struct Store {
+    rows: Vec<u32>,
+}
+
+impl ludi::Actor for Store {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl ludi::BatchHandler<Insert> for Store {
+    async fn handle_batch(
+        &mut self,
+        msgs: Vec<Insert>,
+        _ctx: &mut ludi::Context<Self>,
+    ) -> Vec<u64> {
+        msgs.into_iter()
+            .map(|msg| {
+                self.rows.push(msg.0);
+                self.rows.len() as u64
+            })
+            .collect()
+    }
+}
+
+impl ludi::BatchHandler<Remove> for Store {
+    async fn handle_batch(&mut self, msgs: Vec<Remove>, _ctx: &mut ludi::Context<Self>) -> Vec<()> {
+        self.rows
+            .retain(|row| !msgs.iter().any(|msg| msg.0 == *row));
+        vec![(); msgs.len()]
+    }
+}
+
+#[test]
+fn test() {
+    assert_message::<Insert, u64>();
+    assert_message::<Remove, ()>();
+    assert_batch_handler::<Store, Insert>();
+    assert_batch_handler::<Store, Remove>();
+    assert_wrap::<StoreMsg, Remove>();
+    assert_wrap::<StoreMsg, Insert>();
+}
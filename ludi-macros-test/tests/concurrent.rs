@@ -0,0 +1,72 @@
+#![allow(dead_code)]
+
+use ludi_macros_test::*;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32, concurrent)]
+struct Peek;
+
+#[derive(Default, ludi::Controller)]
+pub struct Store {
+    value: u32,
+}
+
+impl ludi::Actor for Store {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl ludi::Handler<Peek> for Store {
+    async fn handle(&mut self, msg: Peek, _ctx: &mut ludi::Context<Self>) -> u32 {
+        ludi::ConcurrentHandler::handle_concurrent(self, msg).await
+    }
+}
+
+impl ludi::ConcurrentHandler<Peek> for Store {
+    async fn handle_concurrent(&self, _msg: Peek) -> u32 {
+        self.value
+    }
+}
+
+#[ludi::interface]
+trait Read {
+    #[msg(concurrent)]
+    async fn read(&self) -> u32;
+}
+
+#[ludi::implement(ctrl)]
+impl Read for Store {
+    #[msg(concurrent)]
+    async fn read(&self) -> u32 {
+        self.value
+    }
+}
+
+#[ludi::implement]
+#[ctrl]
+#[msg(wrap)]
+impl Store {
+    #[msg(concurrent)]
+    pub async fn get(&self) -> u32 {
+        self.value
+    }
+
+    pub async fn set(&mut self, value: u32) {
+        self.value = value;
+    }
+}
+
+#[test]
+fn test() {
+    assert_message::<Peek, u32>();
+    assert_concurrent_handler::<Store, Peek>();
+    assert_concurrent_handler::<Store, ReadMsgRead>();
+    assert_concurrent_handler::<Store, StoreMsgGet>();
+    assert_handler::<Store, StoreMsgGet>();
+    assert_handler::<Store, StoreMsgSet>();
+    assert_wrap::<StoreMsg, StoreMsgGet>();
+}
@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+
+use ludi::{Message, Priority};
+use ludi_macros_test::*;
+
+#[derive(ludi::Message)]
+#[ludi(priority = high)]
+struct Shutdown;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32, priority = 42)]
+struct Bulk;
+
+#[derive(ludi::Message)]
+struct Plain;
+
+#[derive(ludi::Wrap)]
+enum Control {
+    Shutdown(Shutdown),
+    Bulk(Bulk),
+    Plain(Plain),
+}
+
+#[derive(Default, ludi::Controller)]
+pub struct Service;
+
+impl ludi::Actor for Service {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+#[ludi::implement]
+#[msg(wrap)]
+impl Service {
+    #[msg(priority = low)]
+    pub async fn reload(&self) {}
+
+    pub async fn health(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test() {
+    assert_message::<Shutdown, ()>();
+    assert_message::<Bulk, u32>();
+
+    assert_eq!(Shutdown.priority(), Priority::HIGH);
+    assert_eq!(Bulk.priority(), Priority::new(42));
+    assert_eq!(Plain.priority(), Priority::NORMAL);
+
+    assert_eq!(Control::from(Shutdown).priority(), Priority::HIGH);
+    assert_eq!(Control::from(Bulk).priority(), Priority::new(42));
+
+    assert_eq!(ServiceMsgReload.priority(), Priority::LOW);
+    assert_eq!(ServiceMsgHealth.priority(), Priority::NORMAL);
+    assert_eq!(ServiceMsg::from(ServiceMsgReload).priority(), Priority::LOW);
+}
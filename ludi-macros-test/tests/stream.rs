@@ -0,0 +1,87 @@
+#![allow(dead_code)]
+
+use futures_util::{stream, Stream, StreamExt};
+use ludi_macros_test::*;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32, stream)]
+struct Count(u32);
+
+#[derive(Debug)]
+pub struct StoreError;
+
+#[derive(Default, ludi::Controller)]
+pub struct Store {
+    values: Vec<u32>,
+}
+
+impl ludi::Actor for Store {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl ludi::StreamingHandler<Count> for Store {
+    async fn handle_stream(
+        &mut self,
+        msg: Count,
+        mut items: ludi::StreamSender<u32>,
+        _ctx: &mut ludi::Context<Self>,
+    ) {
+        for i in 0..msg.0 {
+            if items.send(i).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[ludi::interface]
+trait Scan {
+    fn scan(&self, from: u32) -> impl Stream<Item = u32> + Send;
+}
+
+#[ludi::implement(ctrl)]
+impl Scan for Store {
+    fn scan(&self, from: u32) -> impl Stream<Item = u32> + Send {
+        stream::iter(
+            self.values
+                .clone()
+                .into_iter()
+                .filter(move |value| *value >= from),
+        )
+    }
+}
+
+#[ludi::implement]
+#[ctrl]
+#[msg(wrap)]
+impl Store {
+    pub fn values(&self) -> impl Stream<Item = u32> {
+        stream::iter(self.values.clone())
+    }
+
+    #[ctrl(err = "|_| StoreError")]
+    pub fn checked_values(&self) -> impl Stream<Item = Result<u32, StoreError>> {
+        stream::iter(self.values.clone()).map(Ok)
+    }
+
+    pub async fn push(&mut self, value: u32) {
+        self.values.push(value);
+    }
+}
+
+#[test]
+fn test() {
+    assert_message::<Count, ludi::ResponseStream<u32>>();
+    assert_streaming_handler::<Store, Count>();
+    assert_message::<ScanMsgScan, ludi::ResponseStream<u32>>();
+    assert_streaming_handler::<Store, ScanMsgScan>();
+    assert_streaming_handler::<Store, StoreMsgValues>();
+    assert_streaming_handler::<Store, StoreMsgCheckedValues>();
+    assert_handler::<Store, StoreMsgPush>();
+    assert_wrap::<StoreMsg, StoreMsgValues>();
+}
@@ -9,7 +9,7 @@ use syn::parse_quote;
 
 use crate::{
     options::{CtrlOptions, ErrorStrategy, MsgOptions},
-    utils::{extract_output, is_ludi_attr},
+    utils::{extract_output, extract_stream_item, is_ludi_attr},
 };
 
 /// An item method.
@@ -34,8 +34,10 @@ pub(crate) struct Method {
 
     /// Method arguments
     pub(crate) args: Vec<(syn::Ident, syn::Type)>,
-    /// Method return type
+    /// Method return type, or the item type if the method returns a stream
     pub(crate) return_ty: syn::Type,
+    /// Whether the method returns a stream of items
+    pub(crate) is_stream: bool,
     /// Type params from the parent item which are present in the method signature
     pub(crate) type_params: IdentSet,
 
@@ -59,7 +61,7 @@ impl Method {
     ) -> Self {
         Method::check_signature(&span, &sig);
 
-        let (args, return_ty) = Self::extract_args(&sig);
+        let (args, return_ty, is_stream) = Self::extract_args(&sig);
         let type_params = Self::extract_type_params(
             &parent_type_params,
             args.iter().map(|(_, ty)| ty),
@@ -110,6 +112,27 @@ impl Method {
             );
         }
 
+        if msg_options
+            .as_ref()
+            .map(|opts| opts.concurrent.is_present())
+            .unwrap_or(false)
+        {
+            Self::check_concurrent_receiver(&span, &sig);
+
+            if is_stream {
+                emit_error!(span, "streaming methods can not be concurrent");
+            }
+        }
+
+        if is_stream
+            && ctrl_options
+                .as_ref()
+                .map(|opts| opts.timeout.is_some())
+                .unwrap_or(false)
+        {
+            emit_error!(span, "streaming methods do not support a timeout");
+        }
+
         let struct_path =
             if let Some(path) = msg_options.as_ref().and_then(|opts| opts.path.as_ref()) {
                 parse_quote!(#path :: #struct_ident)
@@ -132,6 +155,7 @@ impl Method {
             body,
             args,
             return_ty,
+            is_stream,
             type_params,
             struct_ident,
             struct_path,
@@ -151,8 +175,29 @@ impl Method {
         }
     }
 
-    /// Extracts the method arguments and return type.
-    fn extract_args(sig: &syn::Signature) -> (Vec<(syn::Ident, syn::Type)>, syn::Type) {
+    /// Concurrent messages are handled using a shared reference, so the method must not
+    /// take `&mut self`.
+    fn check_concurrent_receiver(span: &Span, sig: &syn::Signature) {
+        match sig.receiver() {
+            Some(receiver) if receiver.reference.is_some() && receiver.mutability.is_none() => {}
+            Some(receiver) => {
+                emit_error!(receiver, "concurrent methods must take `&self`");
+            }
+            None => {
+                emit_error!(span, "concurrent methods must take `&self`");
+            }
+        }
+    }
+
+    fn is_concurrent(&self) -> bool {
+        self.msg_options
+            .as_ref()
+            .map(|opts| opts.concurrent.is_present())
+            .unwrap_or(false)
+    }
+
+    /// Extracts the method arguments and return type, and whether the method returns a stream.
+    fn extract_args(sig: &syn::Signature) -> (Vec<(syn::Ident, syn::Type)>, syn::Type, bool) {
         let args = sig
             .inputs
             .clone()
@@ -184,11 +229,13 @@ impl Method {
             })
             .collect::<Vec<_>>();
 
-        let return_ty = if let Some(ty) = extract_output(sig) {
-            ty
+        let (return_ty, is_stream) = if let Some(ty) = extract_output(sig) {
+            (ty, false)
+        } else if let Some(ty) = extract_stream_item(sig) {
+            (ty, true)
         } else {
-            emit_error!(sig, "method must be async or return a future.");
-            parse_quote!(())
+            emit_error!(sig, "method must be async or return a future or stream.");
+            (parse_quote!(()), false)
         };
 
         // TODO: better enforce that return type is Sized + Send + 'static
@@ -199,7 +246,7 @@ impl Method {
             _ => {}
         }
 
-        (args, return_ty)
+        (args, return_ty, is_stream)
     }
 
     /// Extracts the type params from the parent item which are present in the method signature.
@@ -272,6 +319,86 @@ impl Method {
             quote!({ #( pub #arg_idents: #arg_tys ),* })
         };
 
+        let priority_method = msg_options
+            .as_ref()
+            .and_then(|opts| opts.priority.as_ref())
+            .map(|priority| {
+                quote!(
+                    fn priority(&self) -> ::ludi::Priority {
+                        #priority
+                    }
+                )
+            });
+
+        if self.is_stream {
+            return quote!(
+                #( #[#msg_attrs] )*
+                #vis struct #struct_ident<#(#type_params),*> #struct_body
+
+                impl<#(#type_params),*> ::ludi::Message for #struct_ident<#(#type_params),*>
+                where
+                    #(#type_params: Send + 'static),*
+                {
+                    type Return = ::ludi::ResponseStream<#return_ty>;
+
+                    #priority_method
+                }
+
+                impl<#(#type_params),*> ::ludi::StreamMessage for #struct_ident<#(#type_params),*>
+                where
+                    #(#type_params: Send + 'static),*
+                {
+                    type Item = #return_ty;
+                }
+
+                impl<A, #(#type_params),*> ::ludi::Dispatch<A> for #struct_ident<#(#type_params),*>
+                where
+                    A: ::ludi::Actor + ::ludi::StreamingHandler<#struct_ident<#(#type_params),*>>,
+                    #(#type_params: Send + 'static),*
+                {
+                    async fn dispatch<R: FnOnce(::ludi::ResponseStream<#return_ty>) + Send>(
+                        self,
+                        actor: &mut A,
+                        ctx: &mut ::ludi::Context<A>,
+                        ret: R,
+                    ) {
+                        ::ludi::StreamingHandler::<#struct_ident<#(#type_params),*>>::process_stream(
+                            actor,
+                            self,
+                            ctx,
+                            ret
+                        ).await;
+                    }
+                }
+            );
+        }
+
+        let (handler_bound, concurrent_methods) = if self.is_concurrent() {
+            (
+                quote!(::ludi::ConcurrentHandler<#struct_ident<#(#type_params),*>>),
+                quote!(
+                    fn is_concurrent(&self) -> bool {
+                        true
+                    }
+
+                    fn dispatch_concurrent(
+                        self,
+                        actor: &A,
+                    ) -> Result<impl ::std::future::Future<Output = #return_ty> + Send + '_, Self> {
+                        Ok(::ludi::ConcurrentHandler::<#struct_ident<#(#type_params),*>>::handle_concurrent(
+                            actor,
+                            self,
+                        ))
+                    }
+                ),
+            )
+        } else {
+            (
+                quote!(::ludi::Handler<#struct_ident<#(#type_params),*>>),
+                quote!(),
+            )
+        };
+
         quote!(
             #( #[#msg_attrs] )*
             #vis struct #struct_ident<#(#type_params),*> #struct_body
@@ -281,11 +408,13 @@ impl Method {
                 #(#type_params: Send + 'static),*
             {
                 type Return = #return_ty;
+
+                #priority_method
             }
 
             impl<A, #(#type_params),*> ::ludi::Dispatch<A> for #struct_ident<#(#type_params),*>
             where
-                A: ::ludi::Actor + ::ludi::Handler<#struct_ident<#(#type_params),*>>,
+                A: ::ludi::Actor + #handler_bound,
                 #(#type_params: Send + 'static),*
             {
                 async fn dispatch<R: FnOnce(#return_ty) + Send>(
@@ -301,6 +430,8 @@ impl Method {
                         ret
                     ).await;
                 }
+
+                #concurrent_methods
             }
         )
     }
@@ -339,6 +470,50 @@ impl Method {
 
         let (impl_generics, _, where_clause) = generics.split_for_impl();
 
+        if self.is_stream {
+            return quote!(
+                impl #impl_generics ::ludi::StreamingHandler<#struct_path<#(#type_params),*>> for #actor_path #where_clause {
+                    #(#attrs)*
+                    async fn handle_stream(
+                        &mut self,
+                        msg: #struct_path<#(#type_params),*>,
+                        items: ::ludi::StreamSender<<#struct_path<#(#type_params),*> as ::ludi::StreamMessage>::Item>,
+                        ctx: &mut ::ludi::Context<Self>
+                    ) {
+                        #destructure
+                        let stream = #body;
+                        // The caller dropping the stream is not an error.
+                        let _ = items.forward(stream).await;
+                    }
+                }
+            );
+        }
+
+        if self.is_concurrent() {
+            return quote!(
+                impl #impl_generics ::ludi::ConcurrentHandler<#struct_path<#(#type_params),*>> for #actor_path #where_clause {
+                    #(#attrs)*
+                    async fn handle_concurrent(
+                        &self,
+                        msg: #struct_path<#(#type_params),*>,
+                    ) -> <#struct_path<#(#type_params),*> as ::ludi::Message>::Return {
+                        #destructure
+                        #body
+                    }
+                }
+
+                impl #impl_generics ::ludi::Handler<#struct_path<#(#type_params),*>> for #actor_path #where_clause {
+                    async fn handle(
+                        &mut self,
+                        msg: #struct_path<#(#type_params),*>,
+                        _ctx: &mut ::ludi::Context<Self>
+                    ) -> <#struct_path<#(#type_params),*> as ::ludi::Message>::Return {
+                        ::ludi::ConcurrentHandler::<#struct_path<#(#type_params),*>>::handle_concurrent(self, msg).await
+                    }
+                }
+            );
+        }
+
         quote!(
             impl #impl_generics ::ludi::Handler<#struct_path<#(#type_params),*>> for #actor_path #where_clause {
                 #(#attrs)*
@@ -395,17 +570,50 @@ impl Method {
             .map(|opts| opts.error_strategy())
             .unwrap_or_default();
 
-        let err_handler = match err_strategy {
+        let err_handler = match &err_strategy {
             ErrorStrategy::Panic => quote!(.expect("message should be handled to completion")),
             ErrorStrategy::Try => quote!(?),
             ErrorStrategy::Map(expr) => quote!(.map_err(#expr)?),
         };
 
+        if self.is_stream {
+            // Items are results when an error strategy is used, so an error is yielded as an
+            // item in place of the items which could not be received.
+            let item_handler = match err_strategy {
+                ErrorStrategy::Panic => quote!(item.expect("message should be handled to completion")),
+                ErrorStrategy::Try => quote!(
+                    item.unwrap_or_else(|err| Err(::core::convert::From::from(err)))
+                ),
+                ErrorStrategy::Map(expr) => quote!(
+                    item.unwrap_or_else(|err| Err(::core::convert::From::from((#expr)(err))))
+                ),
+            };
+
+            return quote!(
+                #(#doc_attrs)*
+                #(#[#attrs])*
+                #vis #ctrl_sig {
+                    ::ludi::__private::StreamExt::map(
+                        self.addr.send_stream(#struct_arg),
+                        |item| #item_handler,
+                    )
+                }
+            );
+        }
+
+        let send = match ctrl_options.as_ref().and_then(|opts| opts.timeout()) {
+            Some(timeout) => {
+                let nanos = timeout.as_nanos() as u64;
+                quote!(self.addr.send_timeout(#struct_arg, ::std::time::Duration::from_nanos(#nanos)))
+            }
+            None => quote!(self.addr.send(#struct_arg)),
+        };
+
         quote!(
             #(#doc_attrs)*
             #(#[#attrs])*
             #vis #ctrl_sig {
-                self.addr.send(#struct_arg).await #err_handler
+                #send.await #err_handler
             }
         )
     }
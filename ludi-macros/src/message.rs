@@ -1,14 +1,20 @@
-use darling::FromDeriveInput;
+use darling::{util::Flag, FromDeriveInput};
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{parse_quote, DeriveInput};
 
+use crate::options::PriorityOption;
+
 #[derive(Debug, FromDeriveInput)]
 #[darling(attributes(ludi))]
 struct Message {
     ident: syn::Ident,
     generics: syn::Generics,
     return_ty: Option<syn::Path>,
+    concurrent: Flag,
+    stream: Flag,
+    batch: Flag,
+    priority: Option<PriorityOption>,
 }
 
 pub(crate) fn impl_message(input: DeriveInput) -> TokenStream {
@@ -16,11 +22,28 @@ pub(crate) fn impl_message(input: DeriveInput) -> TokenStream {
         ident,
         mut generics,
         return_ty,
+        concurrent,
+        stream,
+        batch,
+        priority,
     } = match Message::from_derive_input(&input) {
         Ok(msg) => msg,
         Err(e) => return e.with_span(&input).write_errors(),
     };
 
+    if concurrent.is_present() && stream.is_present() {
+        return syn::Error::new_spanned(&input, "streaming messages can not be concurrent")
+            .to_compile_error();
+    }
+
+    if batch.is_present() && (concurrent.is_present() || stream.is_present()) {
+        return syn::Error::new_spanned(
+            &input,
+            "batched messages can not be concurrent or streaming",
+        )
+        .to_compile_error();
+    }
+
     let generic_params = generics.params.clone();
     let where_clause = generics.make_where_clause();
     for param in generic_params {
@@ -38,9 +61,23 @@ pub(crate) fn impl_message(input: DeriveInput) -> TokenStream {
     dispatch_where
         .predicates
         .push(parse_quote!(A: ::ludi::Actor));
-    dispatch_where
-        .predicates
-        .push(parse_quote!(A: ::ludi::Handler<#ident #ty_generics>));
+    if concurrent.is_present() {
+        dispatch_where
+            .predicates
+            .push(parse_quote!(A: ::ludi::ConcurrentHandler<#ident #ty_generics>));
+    } else if stream.is_present() {
+        dispatch_where
+            .predicates
+            .push(parse_quote!(A: ::ludi::StreamingHandler<#ident #ty_generics>));
+    } else if batch.is_present() {
+        dispatch_where
+            .predicates
+            .push(parse_quote!(A: ::ludi::BatchHandler<#ident #ty_generics>));
+    } else {
+        dispatch_where
+            .predicates
+            .push(parse_quote!(A: ::ludi::Handler<#ident #ty_generics>));
+    }
 
     let (dispatch_generics, _, dispatch_where) = dispatch_generics.split_for_impl();
 
@@ -50,11 +87,82 @@ pub(crate) fn impl_message(input: DeriveInput) -> TokenStream {
         quote!(())
     };
 
+    let (message_return_ty, stream_impl, process) = if stream.is_present() {
+        (
+            quote!(::ludi::ResponseStream<#return_ty>),
+            quote!(
+                impl #impl_generics ::ludi::StreamMessage for #ident #ty_generics #where_clause {
+                    type Item = #return_ty;
+                }
+            ),
+            quote!(actor.process_stream(self, ctx, ret).await;),
+        )
+    } else if batch.is_present() {
+        (
+            return_ty,
+            quote!(),
+            quote!(actor.process_batch(vec![(self, ret)], ctx).await;),
+        )
+    } else {
+        (
+            return_ty,
+            quote!(),
+            quote!(actor.process(self, ctx, ret).await;),
+        )
+    };
+
+    let batch_methods = if batch.is_present() {
+        quote!(
+            fn batch_key(&self) -> Option<::std::any::TypeId> {
+                Some(::std::any::TypeId::of::<Self>())
+            }
+
+            async fn dispatch_batch<R: FnOnce(Self::Return) + Send>(
+                batch: Vec<(Self, R)>,
+                actor: &mut A,
+                ctx: &mut ::ludi::Context<A>,
+            ) {
+                actor.process_batch(batch, ctx).await;
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let concurrent_methods = if concurrent.is_present() {
+        quote!(
+            fn is_concurrent(&self) -> bool {
+                true
+            }
+
+            fn dispatch_concurrent(
+                self,
+                actor: &A,
+            ) -> Result<impl ::std::future::Future<Output = Self::Return> + Send + '_, Self> {
+                Ok(actor.handle_concurrent(self))
+            }
+        )
+    } else {
+        quote!()
+    };
+
+    let priority_method = priority.map(|priority| {
+        quote!(
+            fn priority(&self) -> ::ludi::Priority {
+                #priority
+            }
+        )
+    });
+
     quote!(
         impl #impl_generics ::ludi::Message for #ident #ty_generics #where_clause {
-            type Return = #return_ty;
+            type Return = #message_return_ty;
+
+            #priority_method
         }
 
+        #stream_impl
+
         impl #dispatch_generics ::ludi::Dispatch<A> for #ident #ty_generics #dispatch_where
         {
             async fn dispatch<R: FnOnce(Self::Return) + Send>(
@@ -63,8 +171,12 @@ pub(crate) fn impl_message(input: DeriveInput) -> TokenStream {
                 ctx: &mut ::ludi::Context<A>,
                 ret: R,
             ) {
-                actor.process(self, ctx, ret).await;
+                #process
             }
+
+            #concurrent_methods
+
+            #batch_methods
         }
     )
 }
@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use darling::{
     util::{Flag, Override},
     FromMeta,
 };
+use proc_macro2::TokenStream;
 use proc_macro_error::emit_error;
+use quote::{quote, ToTokens};
 
 #[derive(Default, Clone, FromMeta)]
 pub(crate) struct MsgOptions {
@@ -22,6 +26,10 @@ pub(crate) struct MsgOptions {
     pub(crate) skip_handler: Flag,
     /// Generate messages for a foreign trait
     pub(crate) foreign: Flag,
+    /// Handle the message concurrently using a shared reference
+    pub(crate) concurrent: Flag,
+    /// Default priority of the message
+    pub(crate) priority: Option<PriorityOption>,
 }
 
 impl MsgOptions {
@@ -57,6 +65,14 @@ impl MsgOptions {
         self.skip = other.skip.clone();
         self.skip_handler = other.skip_handler.clone();
         self.foreign = other.foreign.clone();
+
+        if other.concurrent.is_present() {
+            self.concurrent = other.concurrent;
+        }
+
+        if other.priority.is_some() {
+            self.priority = other.priority.clone();
+        }
     }
 
     pub(crate) fn maybe_from_attributes(attrs: &[syn::Attribute]) -> Option<Self> {
@@ -123,6 +139,8 @@ pub(crate) struct CtrlOptions {
     pub(crate) path: Option<syn::Path>,
     /// Error handling
     pub(crate) err: Option<Override<syn::Expr>>,
+    /// Request timeout, eg. "5s"
+    pub(crate) timeout: Option<syn::LitStr>,
 }
 
 impl CtrlOptions {
@@ -146,6 +164,25 @@ impl CtrlOptions {
         if other.err.is_some() {
             self.err = other.err.clone();
         }
+
+        if other.timeout.is_some() {
+            self.timeout = other.timeout.clone();
+        }
+    }
+
+    /// Returns the request timeout, if any.
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        let timeout = self.timeout.as_ref()?;
+        match parse_duration(&timeout.value()) {
+            Some(duration) => Some(duration),
+            None => {
+                emit_error!(
+                    timeout,
+                    "invalid timeout, expected a duration such as \"500ms\" or \"5s\""
+                );
+                None
+            }
+        }
     }
 
     pub(crate) fn error_strategy(&self) -> ErrorStrategy {
@@ -203,6 +240,82 @@ impl Default for ErrorStrategy {
     }
 }
 
+/// A message priority, eg. `priority = high` or `priority = 200`.
+#[derive(Debug, Clone)]
+pub(crate) enum PriorityOption {
+    Low,
+    Normal,
+    High,
+    Value(syn::LitInt),
+}
+
+impl FromMeta for PriorityOption {
+    fn from_string(value: &str) -> darling::Result<Self> {
+        match value {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            _ => Err(darling::Error::unknown_value(value)),
+        }
+    }
+
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        match value {
+            syn::Lit::Int(lit) => lit
+                .base10_parse::<u8>()
+                .map(|_| Self::Value(lit.clone()))
+                .map_err(|err| darling::Error::custom(err).with_span(lit)),
+            syn::Lit::Str(lit) => Self::from_string(&lit.value()).map_err(|e| e.with_span(lit)),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+
+    fn from_expr(expr: &syn::Expr) -> darling::Result<Self> {
+        match expr {
+            syn::Expr::Path(path) => match path.path.get_ident() {
+                Some(ident) => {
+                    Self::from_string(&ident.to_string()).map_err(|e| e.with_span(ident))
+                }
+                None => Err(darling::Error::unexpected_expr_type(expr)),
+            },
+            syn::Expr::Lit(lit) => Self::from_value(&lit.lit),
+            syn::Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+    }
+}
+
+impl ToTokens for PriorityOption {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            Self::Low => quote!(::ludi::Priority::LOW),
+            Self::Normal => quote!(::ludi::Priority::NORMAL),
+            Self::High => quote!(::ludi::Priority::HIGH),
+            Self::Value(value) => quote!(::ludi::Priority::new(#value)),
+        });
+    }
+}
+
+/// Parses a duration with a unit suffix, eg. "500ms", "5s" or "1m".
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+
+    match unit.trim() {
+        "ns" => Some(Duration::from_nanos(amount)),
+        "us" => Some(Duration::from_micros(amount)),
+        "ms" => Some(Duration::from_millis(amount)),
+        "s" => Some(Duration::from_secs(amount)),
+        "m" => amount.checked_mul(60).map(Duration::from_secs),
+        "h" => amount.checked_mul(60 * 60).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub(crate) struct NestedAttrs(Vec<darling::ast::NestedMeta>);
 
@@ -248,6 +361,17 @@ mod tests {
         assert_eq!(options.name.unwrap().to_string(), "Foo");
     }
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5s"), Some(Duration::from_secs(5)));
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("5"), None);
+        assert_eq!(parse_duration("s"), None);
+        assert_eq!(parse_duration("5 days"), None);
+    }
+
     #[test]
     fn test_msg_from_attributes_many() {
         let attrs = vec![
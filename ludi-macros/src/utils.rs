@@ -28,7 +28,7 @@ pub(crate) fn extract_output(sig: &syn::Signature) -> Option<syn::Type> {
             syn::Type::ImplTrait(ty) => {
                 return ty.bounds.iter().find_map(|bound| {
                     if let syn::TypeParamBound::Trait(bound) = bound {
-                        extract_fut_output(bound)
+                        extract_assoc_ty(bound, "Future", "Output")
                     } else {
                         None
                     }
@@ -43,9 +43,34 @@ pub(crate) fn extract_output(sig: &syn::Signature) -> Option<syn::Type> {
     }
 }
 
-fn extract_fut_output(bound: &syn::TraitBound) -> Option<syn::Type> {
+/// Extracts the item type of a function returning `impl Stream`, returns `None` if the
+/// function is async or does not return a stream.
+pub(crate) fn extract_stream_item(sig: &syn::Signature) -> Option<syn::Type> {
+    if sig.asyncness.is_some() {
+        return None;
+    }
+
+    let syn::ReturnType::Type(_, ty) = &sig.output else {
+        return None;
+    };
+
+    let syn::Type::ImplTrait(ty) = ty.as_ref() else {
+        return None;
+    };
+
+    ty.bounds.iter().find_map(|bound| {
+        if let syn::TypeParamBound::Trait(bound) = bound {
+            extract_assoc_ty(bound, "Stream", "Item")
+        } else {
+            None
+        }
+    })
+}
+
+/// Extracts an associated type from a trait bound, eg. `Output` from `Future<Output = T>`.
+fn extract_assoc_ty(bound: &syn::TraitBound, trait_name: &str, assoc: &str) -> Option<syn::Type> {
     let segment = bound.path.segments.last()?;
-    if segment.ident != "Future" {
+    if segment.ident != trait_name {
         return None;
     }
 
@@ -55,7 +80,7 @@ fn extract_fut_output(bound: &syn::TraitBound) -> Option<syn::Type> {
 
     args.args.iter().find_map(|arg| {
         if let syn::GenericArgument::AssocType(assoc_ty) = arg {
-            if assoc_ty.ident == "Output" {
+            if assoc_ty.ident == assoc {
                 return Some(assoc_ty.ty.clone());
             }
         }
@@ -123,6 +123,22 @@ impl ToTokens for Wrap {
         tokens.extend(quote!(
             impl #impl_generics ::ludi::Message for #ident #ty_generics #where_clause {
                 type Return = #return_ident #ty_generics;
+
+                fn priority(&self) -> ::ludi::Priority {
+                    match self {
+                        #(
+                            #ident :: #variant_idents (msg) => ::ludi::Message::priority(msg)
+                        ),*
+                    }
+                }
+
+                fn label(&self) -> &'static str {
+                    match self {
+                        #(
+                            #ident :: #variant_idents (_) => stringify!(#variant_idents)
+                        ),*
+                    }
+                }
             }
     
             #( #[#return_attrs] )*
@@ -159,7 +175,7 @@ impl ToTokens for Wrap {
             .predicates
             .push(parse_quote!(A: ::ludi::Actor));
 
-        for variant_ty in variant_tys {
+        for variant_ty in &variant_tys {
             where_clause
                 .predicates
                 .push(parse_quote!(#variant_ty: ::ludi::Dispatch<A>));
@@ -184,6 +200,76 @@ impl ToTokens for Wrap {
                         ),*
                     }
                 }
+
+                fn is_concurrent(&self) -> bool {
+                    match self {
+                        #(
+                            #ident :: #variant_idents (msg) => ::ludi::Dispatch::<A>::is_concurrent(msg)
+                        ),*
+                    }
+                }
+
+                fn dispatch_concurrent(
+                    self,
+                    actor: &A,
+                ) -> Result<impl ::std::future::Future<Output = Self::Return> + Send + '_, Self>
+                where
+                    A: Sync,
+                {
+                    type Handled<'a, T> = ::std::pin::Pin<::std::boxed::Box<dyn ::std::future::Future<Output = T> + Send + 'a>>;
+
+                    match self {
+                        #(
+                            #ident :: #variant_idents (msg) => match msg.dispatch_concurrent(actor) {
+                                Ok(handled) => Ok(::std::boxed::Box::pin(async move {
+                                    Self::Return :: #variant_idents (handled.await)
+                                }) as Handled<'_, Self::Return>),
+                                Err(msg) => Err(#ident :: #variant_idents (msg)),
+                            }
+                        ),*
+                    }
+                }
+
+                fn batch_key(&self) -> Option<::std::any::TypeId> {
+                    match self {
+                        #(
+                            #ident :: #variant_idents (msg) => ::ludi::Dispatch::<A>::batch_key(msg)
+                        ),*
+                    }
+                }
+
+                #[allow(unreachable_patterns)]
+                async fn dispatch_batch<R: FnOnce(Self::Return) + Send>(
+                    batch: Vec<(Self, R)>,
+                    actor: &mut A,
+                    ctx: &mut ::ludi::Context<A>,
+                ) {
+                    let mut batch = batch.into_iter().peekable();
+                    match batch.peek() {
+                        #(
+                            Some((#ident :: #variant_idents (_), _)) => {
+                                // Messages of another type are dispatched one at a time.
+                                let mut rest = Vec::new();
+                                let batch = batch
+                                    .filter_map(|(msg, ret)| match msg {
+                                        #ident :: #variant_idents (msg) => {
+                                            Some((msg, move |value| ret(Self::Return :: #variant_idents (value))))
+                                        }
+                                        msg => {
+                                            rest.push((msg, ret));
+                                            None
+                                        }
+                                    })
+                                    .collect();
+                                <#variant_tys as ::ludi::Dispatch<A>>::dispatch_batch(batch, actor, ctx).await;
+                                for (msg, ret) in rest {
+                                    ::ludi::Dispatch::<A>::dispatch(msg, actor, ctx, ret).await;
+                                }
+                            }
+                        )*
+                        None => {}
+                    }
+                }
             }
         ));
     }
@@ -0,0 +1,168 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    pin::pin,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use futures_util::{
+    future::{select, Either},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    StreamExt,
+};
+use ludi_core::{futures::QueueFuture, Address, Error, Mailbox, Message, ResponseSender, Wrap};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::frame::{
+    decode_frame, encode_frame, read_frame, write_frame, RemoteEnvelope, RemoteError,
+    RemoteResponse,
+};
+
+/// The number of messages which are queued before they are sent to the remote actor.
+const DEFAULT_CAPACITY: usize = 64;
+
+/// The senders which are waiting for a response, by request id.
+type Pending<T> = Arc<Mutex<HashMap<u64, ResponseSender<T>>>>;
+
+/// An address of an actor in another process.
+///
+/// Messages are queued in a local mailbox, which is forwarded to the remote actor by the
+/// connection. The connection ends once every address to the local mailbox has been dropped
+/// and every response has been received, or once the remote side closes it. Senders which are
+/// still waiting for a response at that point receive [`Error::Disconnected`]. A return value
+/// which can not be decoded is received as [`Error::Wrapper`], see
+/// [`RemoteError::Undecodable`].
+#[derive(Debug, Clone)]
+pub struct RemoteAddress<T: Message> {
+    addr: Address<T>,
+}
+
+impl<T> RemoteAddress<T>
+where
+    T: Message + Serialize,
+    T::Return: DeserializeOwned,
+{
+    /// Creates a new address which sends messages over a connection.
+    ///
+    /// Returns the address and a future which drives the connection, which must be polled
+    /// for messages to be sent. The connection can be any `futures` I/O object, see
+    /// [`RemoteAddress::connect`] and [`RemoteAddress::connect_unix`] to connect with `tokio`.
+    pub fn new<IO>(io: IO) -> (Self, impl Future<Output = io::Result<()>> + Send + 'static)
+    where
+        IO: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (mailbox, addr) = ludi_core::mailbox(DEFAULT_CAPACITY);
+
+        (Self { addr }, drive(io, mailbox))
+    }
+}
+
+impl<T: Message> RemoteAddress<T> {
+    /// Sends a message and waits for a response.
+    pub async fn send<U>(&self, msg: U) -> Result<U::Return, Error>
+    where
+        T: Wrap<U>,
+        U: Message,
+    {
+        self.addr.send(msg).await
+    }
+
+    /// Returns a future which resolves once a message is queued.
+    ///
+    /// The message is queued locally, it is sent to the remote actor by the connection.
+    pub fn queue(&self, msg: T) -> QueueFuture<T> {
+        self.addr.queue(msg)
+    }
+
+    /// Returns whether the connection is still sending messages.
+    pub fn is_connected(&self) -> bool {
+        self.addr.is_connected()
+    }
+
+    /// Returns the local address which messages are forwarded from.
+    ///
+    /// The address can be used like the address of a local actor, eg. to create a controller.
+    pub fn address(&self) -> &Address<T> {
+        &self.addr
+    }
+
+    /// Returns the local address which messages are forwarded from.
+    pub fn into_address(self) -> Address<T> {
+        self.addr
+    }
+}
+
+impl<T: Message> From<RemoteAddress<T>> for Address<T> {
+    fn from(addr: RemoteAddress<T>) -> Self {
+        addr.addr
+    }
+}
+
+fn lock<T: Message>(pending: &Pending<T>) -> MutexGuard<'_, HashMap<u64, ResponseSender<T>>> {
+    pending.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Forwards the messages of a mailbox over a connection, and routes the responses back to
+/// their senders.
+async fn drive<T, IO>(io: IO, mut mailbox: Mailbox<T>) -> io::Result<()>
+where
+    T: Message + Serialize,
+    T::Return: DeserializeOwned,
+    IO: AsyncRead + AsyncWrite,
+{
+    let (mut reader, mut writer) = io.split();
+    let pending: Pending<T> = Default::default();
+
+    let write = async {
+        let mut next_id = 0;
+        while let Some(envelope) = mailbox.next().await {
+            let (msg, sender) = envelope.into_parts();
+            let id = sender.map(|sender| {
+                let id = next_id;
+                next_id += 1;
+                lock(&pending).insert(id, sender);
+                id
+            });
+
+            let buf = encode_frame(&RemoteEnvelope::new(id, msg))?;
+            write_frame(&mut writer, &buf).await?;
+        }
+
+        writer.close().await
+    };
+
+    let read = async {
+        while let Some(buf) = read_frame(&mut reader).await? {
+            let (id, ret) = match decode_frame::<RemoteResponse<T::Return>>(&buf) {
+                Ok(response) => (response.id(), response.into_result()),
+                Err(Some(id)) => (id, Err(RemoteError::Undecodable)),
+                Err(None) => continue,
+            };
+
+            let Some(sender) = lock(&pending).remove(&id) else {
+                continue;
+            };
+
+            match ret {
+                Ok(ret) => sender.send(ret),
+                Err(err) => sender.send_err(err.into()),
+            }
+        }
+
+        Ok(())
+    };
+
+    // Once the local mailbox is closed, keep reading until every response is received.
+    let result = match select(pin!(read), pin!(write)).await {
+        Either::Left((result, _)) => result,
+        Either::Right((Ok(()), read)) => read.await,
+        Either::Right((Err(err), _)) => Err(err),
+    };
+
+    for (_, sender) in lock(&pending).drain() {
+        sender.send_err(Error::Disconnected);
+    }
+
+    result
+}
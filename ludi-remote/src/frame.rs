@@ -0,0 +1,155 @@
+use std::io;
+
+use futures_util::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use ludi_core::Error;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The maximum length of an encoded frame.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// An envelope containing a message which is sent to a remote actor.
+///
+/// If the sender wants a response, the envelope carries a request id which the response is
+/// returned with, see [`RemoteResponse`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteEnvelope<T> {
+    id: Option<u64>,
+    msg: T,
+}
+
+impl<T> RemoteEnvelope<T> {
+    /// Creates a new envelope, with a request id if the sender wants a response.
+    pub fn new(id: Option<u64>, msg: T) -> Self {
+        Self { id, msg }
+    }
+
+    /// Returns the request id.
+    pub fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// Returns the request id and the message.
+    pub fn into_parts(self) -> (Option<u64>, T) {
+        (self.id, self.msg)
+    }
+}
+
+/// The response to a [`RemoteEnvelope`].
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteResponse<R> {
+    id: u64,
+    ret: Result<R, RemoteError>,
+}
+
+impl<R> RemoteResponse<R> {
+    /// Creates a new response to the request with the given id.
+    pub fn new(id: u64, ret: Result<R, RemoteError>) -> Self {
+        Self { id, ret }
+    }
+
+    /// Returns the request id.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the return value of the message, or the error which occurred when sending it.
+    pub fn into_result(self) -> Result<R, RemoteError> {
+        self.ret
+    }
+}
+
+/// An error which occurred when a remote actor received a message.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum RemoteError {
+    /// The message could not be sent to the actor, or was not handled by it.
+    Send(Error),
+    /// The message or its return value could not be decoded, which is received as
+    /// [`Error::Wrapper`].
+    Undecodable,
+}
+
+impl From<Error> for RemoteError {
+    fn from(err: Error) -> Self {
+        RemoteError::Send(err)
+    }
+}
+
+impl From<RemoteError> for Error {
+    fn from(err: RemoteError) -> Self {
+        match err {
+            RemoteError::Send(err) => err,
+            RemoteError::Undecodable => Error::Wrapper,
+        }
+    }
+}
+
+/// The request id of a frame, which can be decoded even if the rest of the frame can not.
+#[derive(Deserialize)]
+struct FrameId {
+    id: Option<u64>,
+}
+
+/// Decodes a frame read with [`read_frame`].
+///
+/// If the frame can not be decoded, returns the request id of the frame if there is one, so
+/// the error can be reported to the sender.
+pub(crate) fn decode_frame<F: DeserializeOwned>(buf: &[u8]) -> Result<F, Option<u64>> {
+    serde_json::from_slice(buf).map_err(|_| {
+        serde_json::from_slice::<FrameId>(buf)
+            .ok()
+            .and_then(|frame| frame.id)
+    })
+}
+
+/// Encodes a frame as JSON, see [`write_frame`].
+pub(crate) fn encode_frame<F: Serialize>(frame: &F) -> io::Result<Vec<u8>> {
+    let buf =
+        serde_json::to_vec(frame).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    if buf.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame exceeds maximum length",
+        ));
+    }
+
+    Ok(buf)
+}
+
+/// Writes an encoded frame, prefixed with its length.
+pub(crate) async fn write_frame<W>(writer: &mut W, buf: &[u8]) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer.write_all(&(buf.len() as u32).to_be_bytes()).await?;
+    writer.write_all(buf).await?;
+    writer.flush().await
+}
+
+/// Reads a frame written with [`write_frame`], returns `None` once the connection is closed.
+///
+/// The frame is decoded separately with [`decode_frame`], so a frame which can not be
+/// decoded does not end the connection.
+pub(crate) async fn read_frame<R>(reader: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = [0; 4];
+    let len = match reader.read_exact(&mut len).await {
+        Ok(()) => u32::from_be_bytes(len) as usize,
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame exceeds maximum length",
+        ));
+    }
+
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf).await?;
+
+    Ok(Some(buf))
+}
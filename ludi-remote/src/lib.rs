@@ -0,0 +1,35 @@
+//! Remote actors for the ludi library.
+//!
+//! Messages are sent to an actor in another process over a connection, eg. a TCP or Unix
+//! domain socket. The server side decodes each [`RemoteEnvelope`] and queues its message in
+//! the actor's local mailbox, see [`serve`]. The client side is a [`RemoteAddress`], which
+//! forwards the messages queued with a local [`Address`](ludi_core::Address) and routes the
+//! responses back to their senders by request id.
+//!
+//! Messages and their return values are encoded with serde. A wrapper message generated with
+//! `Wrap` is sent as a whole, so a controller built on [`RemoteAddress::address`] works the
+//! same as with a local address.
+//!
+//! Connections are `futures` I/O objects, so any runtime can be used. The `tokio` feature,
+//! enabled by default, adds helpers which serve and connect over TCP and Unix domain sockets,
+//! see [`serve_tcp`] and [`RemoteAddress::connect`].
+
+#![deny(unsafe_code)]
+#![deny(unused_must_use)]
+#![deny(missing_docs)]
+#![deny(unreachable_pub)]
+#![deny(clippy::all)]
+
+mod client;
+mod frame;
+#[cfg(feature = "tokio")]
+mod net;
+mod server;
+
+pub use client::RemoteAddress;
+pub use frame::{RemoteEnvelope, RemoteError, RemoteResponse};
+#[cfg(feature = "tokio")]
+pub use net::serve_tcp;
+#[cfg(all(feature = "tokio", unix))]
+pub use net::serve_unix;
+pub use server::serve;
@@ -0,0 +1,116 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_util::io::{AsyncRead, AsyncWrite};
+use ludi_core::{Address, Message};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{
+    io::ReadBuf,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use crate::{serve, RemoteAddress};
+
+/// Serves a local actor to every connection accepted by a TCP listener, see [`serve`].
+///
+/// Each connection is served by a task spawned on the current `tokio` runtime. Returns if a
+/// connection can not be accepted.
+pub async fn serve_tcp<T>(listener: TcpListener, addr: Address<T>) -> io::Result<()>
+where
+    T: Message + DeserializeOwned,
+    T::Return: Serialize,
+{
+    loop {
+        let (stream, _) = listener.accept().await?;
+        stream.set_nodelay(true)?;
+        tokio::spawn(serve(TokioIo(stream), addr.clone()));
+    }
+}
+
+/// Serves a local actor to every connection accepted by a Unix domain socket listener, see
+/// [`serve`].
+///
+/// Each connection is served by a task spawned on the current `tokio` runtime. Returns if a
+/// connection can not be accepted.
+#[cfg(unix)]
+pub async fn serve_unix<T>(listener: tokio::net::UnixListener, addr: Address<T>) -> io::Result<()>
+where
+    T: Message + DeserializeOwned,
+    T::Return: Serialize,
+{
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(serve(TokioIo(stream), addr.clone()));
+    }
+}
+
+impl<T> RemoteAddress<T>
+where
+    T: Message + Serialize,
+    T::Return: DeserializeOwned,
+{
+    /// Connects to a remote actor over TCP, see [`serve_tcp`].
+    ///
+    /// The connection is driven by a task spawned on the current `tokio` runtime.
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self::spawn(TokioIo(stream)))
+    }
+
+    /// Connects to a remote actor over a Unix domain socket, see [`serve_unix`].
+    ///
+    /// The connection is driven by a task spawned on the current `tokio` runtime.
+    #[cfg(unix)]
+    pub async fn connect_unix(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        let stream = tokio::net::UnixStream::connect(path).await?;
+
+        Ok(Self::spawn(TokioIo(stream)))
+    }
+
+    fn spawn<IO>(io: IO) -> Self
+    where
+        IO: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (addr, conn) = Self::new(io);
+        tokio::spawn(conn);
+        addr
+    }
+}
+
+/// A `tokio` I/O object, which implements the `futures` I/O traits.
+struct TokioIo<T>(T);
+
+impl<T: tokio::io::AsyncRead + Unpin> AsyncRead for TokioIo<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = ReadBuf::new(buf);
+        ready!(Pin::new(&mut self.0).poll_read(cx, &mut buf))?;
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+}
+
+impl<T: tokio::io::AsyncWrite + Unpin> AsyncWrite for TokioIo<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
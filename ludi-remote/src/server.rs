@@ -0,0 +1,108 @@
+use std::io;
+
+use futures_channel::mpsc;
+use futures_core::future::BoxFuture;
+use futures_util::{
+    future::{self, try_join},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    select,
+    stream::FuturesUnordered,
+    FutureExt, SinkExt, StreamExt,
+};
+use ludi_core::{Address, Envelope, Message};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::frame::{
+    decode_frame, encode_frame, read_frame, write_frame, RemoteEnvelope, RemoteError,
+    RemoteResponse,
+};
+
+/// A response which is being waited for, with the id of its request.
+type PendingResponse<R> = BoxFuture<'static, (u64, Result<R, RemoteError>)>;
+
+/// Serves a local actor over a connection, see [`RemoteAddress`](crate::RemoteAddress).
+///
+/// Messages are queued in the mailbox of `addr` in the order they are received, and the
+/// responses are sent back as soon as they are ready. A message which can not be decoded is
+/// answered with [`RemoteError::Undecodable`], or skipped if it does not want a response.
+/// Returns once the remote side closes the connection and every response has been sent, or
+/// once an I/O error occurs.
+///
+/// The connection can be any `futures` I/O object, see [`serve_tcp`](crate::serve_tcp) and
+/// [`serve_unix`](crate::serve_unix) to serve connections with `tokio`.
+pub async fn serve<T, IO>(io: IO, addr: Address<T>) -> io::Result<()>
+where
+    T: Message + DeserializeOwned,
+    T::Return: Serialize,
+    IO: AsyncRead + AsyncWrite,
+{
+    let (mut reader, mut writer) = io.split();
+    let (responses_tx, mut responses_rx) = mpsc::unbounded::<PendingResponse<T::Return>>();
+
+    let read = async move {
+        let mut addr = addr;
+        while let Some(buf) = read_frame(&mut reader).await? {
+            let response = match decode_frame::<RemoteEnvelope<T>>(&buf) {
+                Ok(envelope) => {
+                    let (id, msg) = envelope.into_parts();
+                    queue(&mut addr, id, msg).await
+                }
+                // The sender is told the message could not be decoded, if it wants a response.
+                Err(id) => id.map(|id| future::ready((id, Err(RemoteError::Undecodable))).boxed()),
+            };
+            let Some(response) = response else {
+                continue;
+            };
+
+            // The writer only stops receiving responses if the connection failed.
+            if responses_tx.unbounded_send(response).is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    };
+
+    let write = async move {
+        let mut pending = FuturesUnordered::new();
+        loop {
+            select! {
+                response = responses_rx.next() => {
+                    if let Some(response) = response {
+                        pending.push(response);
+                    }
+                }
+                response = pending.next() => {
+                    if let Some((id, ret)) = response {
+                        let buf = encode_frame(&RemoteResponse::new(id, ret))?;
+                        write_frame(&mut writer, &buf).await?;
+                    }
+                }
+                complete => break,
+            }
+        }
+
+        writer.close().await
+    };
+
+    try_join(read, write).await.map(|_| ())
+}
+
+/// Queues a message in the mailbox of `addr`, returning its response if the sender wants one.
+async fn queue<T: Message>(
+    addr: &mut Address<T>,
+    id: Option<u64>,
+    msg: T,
+) -> Option<PendingResponse<T::Return>> {
+    let Some(id) = id else {
+        // The sender does not want a response, so an error can not be reported.
+        _ = SinkExt::send(addr, Envelope::new(msg)).await;
+        return None;
+    };
+
+    let (envelope, response) = Envelope::new_with_response(msg);
+    Some(match SinkExt::send(addr, envelope).await {
+        Ok(()) => async move { (id, response.await.map_err(RemoteError::from)) }.boxed(),
+        Err(err) => future::ready((id, Err(err.into()))).boxed(),
+    })
+}
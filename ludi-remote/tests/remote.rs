@@ -0,0 +1,193 @@
+use ludi::{Actor, Error};
+use ludi_remote::{serve_tcp, RemoteAddress, RemoteEnvelope, RemoteError, RemoteResponse};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+#[derive(Default, ludi::Controller)]
+pub struct Counter {
+    count: u32,
+}
+
+impl Actor for Counter {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+#[ludi::implement]
+#[ctrl]
+#[msg(
+    attrs(derive(Serialize, Deserialize)),
+    wrap(attrs(
+        derive(Serialize, Deserialize),
+        ludi(return_attrs(derive(Serialize, Deserialize)))
+    ))
+)]
+impl Counter {
+    pub async fn add(&mut self, n: u32) -> u32 {
+        self.count += n;
+        self.count
+    }
+
+    pub async fn get(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Spawns a counter which is served over TCP, returns the address it is served at.
+async fn spawn_counter() -> std::net::SocketAddr {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Counter::default(), &mut mailbox).await });
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let local_addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_tcp(listener, addr));
+
+    local_addr
+}
+
+#[tokio::test]
+async fn test_remote_controller() {
+    let addr = RemoteAddress::<CounterMsg>::connect(spawn_counter().await)
+        .await
+        .unwrap();
+    let ctrl = Counter::controller(addr.address().clone());
+
+    assert_eq!(ctrl.add(1).await, 1);
+    assert_eq!(ctrl.add(2).await, 3);
+    assert_eq!(ctrl.get().await, 3);
+}
+
+#[tokio::test]
+async fn test_remote_send_queue() {
+    let addr = RemoteAddress::<CounterMsg>::connect(spawn_counter().await)
+        .await
+        .unwrap();
+
+    for _ in 0..3 {
+        addr.queue(CounterMsgAdd { n: 2 }.into()).await.unwrap();
+    }
+
+    // Messages are handled in the order they were sent.
+    assert_eq!(addr.send(CounterMsgGet {}).await.unwrap(), 6);
+}
+
+#[tokio::test]
+async fn test_remote_concurrent_senders() {
+    let addr = RemoteAddress::<CounterMsg>::connect(spawn_counter().await)
+        .await
+        .unwrap();
+
+    let sends = (0..10).map(|_| addr.send(CounterMsgAdd { n: 1 }));
+    let mut totals = futures_util::future::try_join_all(sends).await.unwrap();
+    totals.sort();
+
+    assert_eq!(totals, (1..=10).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_remote_disconnected() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let local_addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        // Accept the connection and close it without handling any messages.
+        drop(listener.accept().await.unwrap());
+    });
+
+    let addr = RemoteAddress::<CounterMsg>::connect(local_addr)
+        .await
+        .unwrap();
+    server.await.unwrap();
+
+    // The connection drops the local mailbox once the remote side has closed it.
+    addr.address().closed().await;
+    assert!(!addr.is_connected());
+
+    let err = addr.send(CounterMsgGet {}).await.unwrap_err();
+    assert_eq!(err, Error::Disconnected);
+}
+
+#[tokio::test]
+async fn test_remote_error() {
+    // The actor is not running, so its mailbox is closed.
+    let (mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    drop(mailbox);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let local_addr = listener.local_addr().unwrap();
+    tokio::spawn(serve_tcp(listener, addr));
+
+    let addr = RemoteAddress::<CounterMsg>::connect(local_addr)
+        .await
+        .unwrap();
+
+    // The error is returned by the server, the connection is still open.
+    assert_eq!(
+        addr.send(CounterMsgGet {}).await.unwrap_err(),
+        Error::Disconnected
+    );
+    assert!(addr.is_connected());
+}
+
+#[tokio::test]
+async fn test_remote_undecodable() {
+    let mut stream = TcpStream::connect(spawn_counter().await).await.unwrap();
+
+    async fn write(stream: &mut TcpStream, buf: &[u8]) {
+        stream.write_u32(buf.len() as u32).await.unwrap();
+        stream.write_all(buf).await.unwrap();
+    }
+
+    async fn read(stream: &mut TcpStream) -> RemoteResponse<CounterMsgReturn> {
+        let mut buf = vec![0; stream.read_u32().await.unwrap() as usize];
+        stream.read_exact(&mut buf).await.unwrap();
+        serde_json::from_slice(&buf).unwrap()
+    }
+
+    // Frames which can not be decoded are answered if they have a request id, and skipped
+    // otherwise, without closing the connection.
+    write(&mut stream, b"not json").await;
+    write(&mut stream, br#"{"id":1,"msg":"unknown"}"#).await;
+    let response = read(&mut stream).await;
+    assert_eq!(response.id(), 1);
+    assert!(matches!(
+        response.into_result(),
+        Err(RemoteError::Undecodable)
+    ));
+
+    let envelope = RemoteEnvelope::new(Some(2), CounterMsg::from(CounterMsgAdd { n: 3 }));
+    write(&mut stream, &serde_json::to_vec(&envelope).unwrap()).await;
+    let response = read(&mut stream).await;
+    assert_eq!(response.id(), 2);
+    assert!(matches!(
+        response.into_result(),
+        Ok(CounterMsgReturn::CounterMsgAdd(3))
+    ));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_remote_unix() {
+    let path = std::env::temp_dir().join(format!("ludi-remote-{}.sock", std::process::id()));
+    _ = std::fs::remove_file(&path);
+
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Counter::default(), &mut mailbox).await });
+
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    tokio::spawn(ludi_remote::serve_unix(listener, addr));
+
+    let addr = RemoteAddress::<CounterMsg>::connect_unix(&path)
+        .await
+        .unwrap();
+    assert_eq!(addr.send(CounterMsgAdd { n: 5 }).await.unwrap(), 5);
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -4,6 +4,12 @@ pub use ludi_core::*;
 #[cfg(feature = "macros")]
 pub use ludi_macros::*;
 
+/// Items used by the generated code of the macros, not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use futures_util::StreamExt;
+}
+
 pub mod prelude {
     pub use ludi_core::{Actor, Address, Context, Handler, Mailbox};
 }
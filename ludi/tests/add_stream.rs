@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use futures_util::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use ludi::{Actor, Context, Handler, StreamHandler};
+use tokio::sync::{mpsc, oneshot};
+
+#[derive(ludi::Message)]
+struct Tick(u32);
+
+#[derive(ludi::Message)]
+struct Attach(BoxStream<'static, Tick>);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Total;
+
+#[derive(ludi::Wrap)]
+enum CounterMsg {
+    Attach(Attach),
+    Total(Total),
+}
+
+#[derive(Default)]
+struct Counter {
+    total: u32,
+    finished: Option<oneshot::Sender<u32>>,
+}
+
+impl Actor for Counter {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Tick> for Counter {
+    async fn handle(&mut self, msg: Tick, _ctx: &mut Context<Self>) {
+        self.total += msg.0;
+    }
+}
+
+impl StreamHandler<Tick> for Counter {
+    fn finished(&mut self, _ctx: &mut Context<Self>) {
+        if let Some(finished) = self.finished.take() {
+            _ = finished.send(self.total);
+        }
+    }
+}
+
+impl Handler<Attach> for Counter {
+    async fn handle(&mut self, msg: Attach, ctx: &mut Context<Self>) {
+        ctx.add_stream(msg.0);
+    }
+}
+
+impl Handler<Total> for Counter {
+    async fn handle(&mut self, _msg: Total, _ctx: &mut Context<Self>) -> u32 {
+        self.total
+    }
+}
+
+#[tokio::test]
+async fn test_add_stream() {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    let (tx, rx) = mpsc::unbounded_channel();
+    let (finished_tx, finished_rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let mut counter = Counter {
+            finished: Some(finished_tx),
+            ..Default::default()
+        };
+        ludi::run(&mut counter, &mut mailbox).await
+    });
+
+    let ticks = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|tick| (tick, rx))
+    });
+    addr.send(Attach(ticks.boxed())).await.unwrap();
+
+    for i in 1..=4 {
+        tx.send(Tick(i)).unwrap();
+    }
+    drop(tx);
+
+    // Every item is handled before the stream is finished.
+    assert_eq!(finished_rx.await.unwrap(), 10);
+    assert_eq!(addr.send(Total).await, Ok(10));
+}
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Ping(u32);
+
+struct Busy;
+
+impl Actor for Busy {
+    type Stop = ();
+    type Error = ();
+
+    fn started(&mut self, ctx: &mut Context<Self>) -> Result<(), Self::Error> {
+        ctx.add_stream(stream::repeat_with(|| Tick(1)));
+        Ok(())
+    }
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Tick> for Busy {
+    async fn handle(&mut self, _msg: Tick, _ctx: &mut Context<Self>) {
+        tokio::task::yield_now().await;
+    }
+}
+
+impl StreamHandler<Tick> for Busy {}
+
+impl Handler<Ping> for Busy {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        msg.0
+    }
+}
+
+#[tokio::test]
+async fn test_add_stream_fair() {
+    let (mut mailbox, addr) = ludi::mailbox::<Ping>(8);
+    tokio::spawn(async move { ludi::run(&mut Busy, &mut mailbox).await });
+
+    // An endless stream does not starve the mailbox.
+    for i in 0..8 {
+        let res = tokio::time::timeout(Duration::from_secs(5), addr.send(Ping(i)))
+            .await
+            .expect("mailbox should not be starved by the stream");
+        assert_eq!(res, Ok(i));
+    }
+}
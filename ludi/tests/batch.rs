@@ -0,0 +1,260 @@
+use futures_util::future::BoxFuture;
+use ludi::{Actor, BatchHandler, Context, Envelope, Error, Handler, Layer, Next, Runner, StopMode};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32, batch)]
+struct Write(u32);
+
+#[derive(ludi::Message)]
+#[ludi(batch)]
+struct Log;
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Write,
+    Log,
+}
+
+/// The kind and size of every batch which was handled.
+type BatchLog = Vec<(Kind, usize)>;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = BatchLog)]
+struct Batches;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = bool)]
+struct Canceled;
+
+#[derive(ludi::Message)]
+struct Drain;
+
+#[derive(ludi::Wrap)]
+enum DbMsg {
+    Write(Write),
+    Log(Log),
+    Batches(Batches),
+    Canceled(Canceled),
+    Drain(Drain),
+}
+
+#[derive(Default)]
+struct Db {
+    batches: BatchLog,
+    /// Whether the last batch of writes was canceled.
+    canceled: bool,
+}
+
+impl Actor for Db {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl BatchHandler<Write> for Db {
+    async fn handle_batch(&mut self, msgs: Vec<Write>, ctx: &mut Context<Self>) -> Vec<u32> {
+        self.batches.push((Kind::Write, msgs.len()));
+        self.canceled = ctx.is_canceled();
+        msgs.into_iter().map(|msg| msg.0 * 2).collect()
+    }
+}
+
+impl BatchHandler<Log> for Db {
+    async fn handle_batch(&mut self, msgs: Vec<Log>, _ctx: &mut Context<Self>) -> Vec<()> {
+        self.batches.push((Kind::Log, msgs.len()));
+        vec![(); msgs.len()]
+    }
+}
+
+impl Handler<Batches> for Db {
+    async fn handle(&mut self, _msg: Batches, _ctx: &mut Context<Self>) -> BatchLog {
+        std::mem::take(&mut self.batches)
+    }
+}
+
+impl Handler<Canceled> for Db {
+    async fn handle(&mut self, _msg: Canceled, _ctx: &mut Context<Self>) -> bool {
+        self.canceled
+    }
+}
+
+impl Handler<Drain> for Db {
+    async fn handle(&mut self, _msg: Drain, ctx: &mut Context<Self>) {
+        ctx.stop_with(StopMode::Drain);
+    }
+}
+
+/// Drops empty writes, and increments the value returned for the others.
+struct Increment;
+
+impl Layer<DbMsg> for Increment {
+    fn dispatch<'a>(
+        &'a self,
+        msg: DbMsg,
+        next: Next<'a, DbMsg>,
+    ) -> BoxFuture<'a, Option<DbMsgReturn>> {
+        Box::pin(async move {
+            match msg {
+                DbMsg::Write(Write(0)) => None,
+                msg => match next.run(msg).await? {
+                    DbMsgReturn::Write(value) => Some(DbMsgReturn::Write(value + 1)),
+                    ret => Some(ret),
+                },
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_batch() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<DbMsg>(16);
+
+    // Queue the messages before the actor is running, so they are all ready at once.
+    let mut writes = Vec::new();
+    for i in 0..3 {
+        writes.push(addr.try_send(Write(i).into()).unwrap());
+    }
+    addr.try_queue(Log.into()).unwrap();
+    addr.try_queue(Log.into()).unwrap();
+    writes.push(addr.try_send(Write(3).into()).unwrap());
+
+    tokio::spawn(async move { ludi::run(&mut Db::default(), &mut mailbox).await });
+
+    // Every response is routed back to its own sender.
+    for (i, write) in writes.into_iter().enumerate() {
+        let ret = write.await.unwrap();
+        assert!(matches!(ret, DbMsgReturn::Write(value) if value == i as u32 * 2));
+    }
+
+    // Batches never reorder messages, so they end at a message of another type.
+    assert_eq!(
+        addr.send(Batches).await.unwrap(),
+        vec![(Kind::Write, 3), (Kind::Log, 2), (Kind::Write, 1)]
+    );
+
+    // A single message is handled as a batch of one.
+    assert_eq!(addr.send(Write(5)).await, Ok(10));
+    assert_eq!(addr.send(Batches).await.unwrap(), vec![(Kind::Write, 1)]);
+}
+
+#[tokio::test]
+async fn test_max_batch_size() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<DbMsg>(16);
+
+    for i in 0..5 {
+        addr.try_queue(Write(i).into()).unwrap();
+    }
+
+    tokio::spawn(async move {
+        Runner::new()
+            .with_max_batch_size(2)
+            .run(&mut Db::default(), &mut mailbox)
+            .await
+    });
+
+    assert_eq!(
+        addr.send(Batches).await.unwrap(),
+        vec![(Kind::Write, 2), (Kind::Write, 2), (Kind::Write, 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_batch_layer() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<DbMsg>(16);
+
+    let writes = (0..4)
+        .map(|i| addr.try_send(Write(i).into()).unwrap())
+        .collect::<Vec<_>>();
+
+    tokio::spawn(async move {
+        Runner::new()
+            .with_layer(Increment)
+            .run(&mut Db::default(), &mut mailbox)
+            .await
+    });
+
+    // Every message passes through the layer, the messages it drops are not handled.
+    let mut writes = writes.into_iter();
+    assert!(matches!(
+        writes.next().unwrap().await,
+        Err(Error::Interrupted)
+    ));
+    for (i, write) in writes.enumerate() {
+        let ret = write.await.unwrap();
+        assert!(matches!(ret, DbMsgReturn::Write(value) if value == (i as u32 + 1) * 2 + 1));
+    }
+
+    assert_eq!(addr.send(Batches).await.unwrap(), vec![(Kind::Write, 3)]);
+}
+
+#[tokio::test]
+async fn test_batch_canceled() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<DbMsg>(16);
+
+    // A batch is only canceled once every sender has stopped waiting.
+    let write = addr.try_send(Write(1).into()).unwrap();
+    drop(addr.try_send(Write(2).into()).unwrap());
+    let partial = addr.try_send(Canceled.into()).unwrap();
+    drop(addr.try_send(Write(3).into()).unwrap());
+    drop(addr.try_send(Write(4).into()).unwrap());
+
+    tokio::spawn(async move { ludi::run(&mut Db::default(), &mut mailbox).await });
+
+    assert!(matches!(write.await, Ok(DbMsgReturn::Write(2))));
+    assert!(matches!(partial.await, Ok(DbMsgReturn::Canceled(false))));
+    assert!(addr.send(Canceled).await.unwrap());
+}
+
+/// Messages which are not handled in batches, or which are of another type than the rest of
+/// the batch, are dispatched one at a time.
+#[tokio::test]
+async fn test_dispatch_batch_fallback() {
+    let mut db = Db::default();
+    let mut ctx = Context::default();
+
+    let (write, write_response) = Envelope::new_with_response(DbMsg::from(Write(1)));
+    let (log, log_response) = Envelope::new_with_response(DbMsg::from(Log));
+    Envelope::dispatch_batch(vec![write, log], &mut db, &mut ctx).await;
+    assert!(matches!(write_response.await, Ok(DbMsgReturn::Write(2))));
+    assert!(matches!(log_response.await, Ok(DbMsgReturn::Log(()))));
+
+    let (canceled, canceled_response) = Envelope::new_with_response(DbMsg::from(Canceled));
+    let (batches, batches_response) = Envelope::new_with_response(DbMsg::from(Batches));
+    Envelope::dispatch_batch(vec![canceled, batches], &mut db, &mut ctx).await;
+    assert!(matches!(
+        canceled_response.await,
+        Ok(DbMsgReturn::Canceled(false))
+    ));
+    assert!(matches!(
+        batches_response.await,
+        Ok(DbMsgReturn::Batches(batches)) if batches == [(Kind::Write, 1), (Kind::Log, 1)]
+    ));
+}
+
+#[tokio::test]
+async fn test_batch_drain() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<DbMsg>(16);
+
+    addr.try_queue(Drain.into()).unwrap();
+    let writes = (0..3)
+        .map(|i| addr.try_send(Write(i).into()).unwrap())
+        .collect::<Vec<_>>();
+    drop(addr.try_send(Write(3).into()).unwrap());
+
+    let mut db = Db::default();
+    Runner::new()
+        .with_skip_canceled(true)
+        .run(&mut db, &mut mailbox)
+        .await
+        .unwrap();
+
+    // Queued messages are drained in batches, skipping the canceled ones.
+    for (i, write) in writes.into_iter().enumerate() {
+        let ret = write.await.unwrap();
+        assert!(matches!(ret, DbMsgReturn::Write(value) if value == i as u32 * 2));
+    }
+    assert_eq!(db.batches, vec![(Kind::Write, 3)]);
+}
@@ -0,0 +1,104 @@
+use ludi::{Actor, Broker, Context, Handler, Topic};
+
+#[derive(Clone, ludi::Message)]
+struct Event(u32);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Total;
+
+#[derive(ludi::Wrap)]
+enum ListenerMsg {
+    Event(Event),
+    Total(Total),
+}
+
+#[derive(Default)]
+struct Listener {
+    total: u32,
+}
+
+impl Actor for Listener {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Event> for Listener {
+    async fn handle(&mut self, msg: Event, _ctx: &mut Context<Self>) {
+        self.total += msg.0;
+    }
+}
+
+impl Handler<Total> for Listener {
+    async fn handle(&mut self, _msg: Total, _ctx: &mut Context<Self>) -> u32 {
+        self.total
+    }
+}
+
+fn spawn_listener() -> ludi::Address<ListenerMsg> {
+    let (mut mailbox, addr) = ludi::mailbox::<ListenerMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Listener::default(), &mut mailbox).await });
+    addr
+}
+
+#[tokio::test]
+async fn test_topic_publish() {
+    let topic = Topic::<Event>::new();
+
+    let a = spawn_listener();
+    let b = spawn_listener();
+    topic.subscribe(a.downgrade());
+    let id = topic.subscribe(b.downgrade());
+
+    assert_eq!(topic.publish(Event(1)).await, 2);
+
+    assert!(topic.unsubscribe(id));
+    assert_eq!(topic.publish(Event(2)).await, 1);
+
+    assert_eq!(a.send(Total).await.unwrap(), 3);
+    assert_eq!(b.send(Total).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_topic_prune_disconnected() {
+    let topic = Topic::<Event>::new();
+
+    let (mailbox, addr) = ludi::mailbox::<ListenerMsg>(8);
+    let listener = spawn_listener();
+    topic.subscribe(addr.downgrade());
+    topic.subscribe(listener.downgrade());
+    drop(mailbox);
+
+    assert_eq!(topic.publish(Event(1)).await, 1);
+    assert_eq!(topic.len(), 1);
+}
+
+#[tokio::test]
+async fn test_topic_prune_dropped() {
+    let topic = Topic::<Event>::new();
+
+    let addr = spawn_listener();
+    topic.subscribe(addr.downgrade());
+    assert_eq!(topic.publish(Event(1)).await, 1);
+
+    // The topic does not keep the listener running.
+    drop(addr);
+    assert_eq!(topic.publish(Event(2)).await, 0);
+    assert!(topic.is_empty());
+}
+
+#[tokio::test]
+async fn test_broker() {
+    let broker = Broker::new();
+
+    let addr = spawn_listener();
+    broker.subscribe::<Event>(addr.downgrade());
+
+    assert_eq!(broker.publish(Event(5)).await, 1);
+    assert_eq!(broker.topic::<Event>().len(), 1);
+    assert_eq!(addr.send(Total).await.unwrap(), 5);
+}
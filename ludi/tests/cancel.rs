@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use ludi::{futures::ResponseFuture, Actor, Context, Error, Handler, Runner};
+use tokio::sync::oneshot;
+
+#[derive(ludi::Message)]
+struct Block(oneshot::Receiver<()>);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Query;
+
+#[derive(ludi::Message)]
+struct Slow;
+
+/// The number of handled and canceled queries.
+type Counts = (u32, u32);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = Counts)]
+struct Stats;
+
+#[derive(ludi::Wrap)]
+enum QueryMsg {
+    Block(Block),
+    Query(Query),
+    Slow(Slow),
+    Stats(Stats),
+}
+
+#[derive(Default)]
+struct Database {
+    queries: u32,
+    canceled: u32,
+}
+
+impl Actor for Database {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Block> for Database {
+    async fn handle(&mut self, msg: Block, _ctx: &mut Context<Self>) {
+        _ = msg.0.await;
+    }
+}
+
+impl Handler<Query> for Database {
+    async fn handle(&mut self, _msg: Query, _ctx: &mut Context<Self>) -> u32 {
+        self.queries += 1;
+        self.queries
+    }
+}
+
+impl Handler<Slow> for Database {
+    async fn handle(&mut self, _msg: Slow, ctx: &mut Context<Self>) {
+        assert!(!ctx.is_canceled());
+        ctx.cancellation().await;
+        assert!(ctx.is_canceled());
+        self.canceled += 1;
+    }
+}
+
+impl Handler<Stats> for Database {
+    async fn handle(&mut self, _msg: Stats, _ctx: &mut Context<Self>) -> Counts {
+        (self.queries, self.canceled)
+    }
+}
+
+/// Sends a query which is abandoned while the actor is busy, returns the stats afterwards.
+async fn abandoned_query(runner: Runner) -> Counts {
+    let (mut mailbox, addr) = ludi::mailbox::<QueryMsg>(8);
+    tokio::spawn(async move { runner.run(&mut Database::default(), &mut mailbox).await });
+
+    let (unblock, blocked) = oneshot::channel();
+    addr.queue(Block(blocked).into()).await.unwrap();
+
+    let response = addr.wait(Query.into()).detach().await.unwrap();
+    drop(response);
+    unblock.send(()).unwrap();
+
+    addr.send(Stats).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_skip_canceled() {
+    assert_eq!(abandoned_query(Runner::new()).await, (1, 0));
+    assert_eq!(
+        abandoned_query(Runner::new().with_skip_canceled(true)).await,
+        (0, 0)
+    );
+}
+
+#[tokio::test]
+async fn test_cancellation() {
+    let (mut mailbox, addr) = ludi::mailbox::<QueryMsg>(8);
+    tokio::spawn(async move {
+        Runner::new()
+            .run(&mut Database::default(), &mut mailbox)
+            .await
+    });
+
+    let res = addr
+        .wait(Slow.into())
+        .timeout(Duration::from_millis(20))
+        .await;
+    assert_eq!(res.err(), Some(Error::Timeout));
+
+    assert_eq!(addr.send(Stats).await.unwrap(), (0, 1));
+}
+
+#[tokio::test]
+async fn test_response_sender() {
+    let (sender, response) = ResponseFuture::<QueryMsg>::new();
+    drop(sender);
+    assert_eq!(response.await.err(), Some(Error::Interrupted));
+
+    let (mut sender, response) = ResponseFuture::<QueryMsg>::new();
+    assert!(!sender.is_canceled());
+    drop(response);
+    sender.cancellation().await;
+    assert!(sender.is_canceled());
+}
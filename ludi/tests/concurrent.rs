@@ -0,0 +1,99 @@
+use std::{sync::Arc, time::Duration};
+
+use ludi::{Actor, ConcurrentHandler, Context, Envelope, Handler};
+use tokio::sync::Barrier;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = usize, concurrent)]
+struct Read(Arc<Barrier>);
+
+#[derive(ludi::Message)]
+struct Write;
+
+#[derive(ludi::Wrap)]
+enum StoreMsg {
+    Read(Read),
+    Write(Write),
+}
+
+#[derive(Default)]
+struct Store {
+    value: usize,
+}
+
+impl Actor for Store {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Read> for Store {
+    async fn handle(&mut self, msg: Read, _ctx: &mut Context<Self>) -> usize {
+        self.handle_concurrent(msg).await
+    }
+}
+
+impl ConcurrentHandler<Read> for Store {
+    async fn handle_concurrent(&self, msg: Read) -> usize {
+        msg.0.wait().await;
+        self.value
+    }
+}
+
+impl Handler<Write> for Store {
+    async fn handle(&mut self, _msg: Write, _ctx: &mut Context<Self>) {
+        self.value += 1;
+    }
+}
+
+#[tokio::test]
+async fn test_run_concurrent() {
+    let (mut mailbox, addr) = ludi::mailbox::<StoreMsg>(8);
+    let mut actor = Store::default();
+
+    tokio::spawn(async move { ludi::run_concurrent(&mut actor, &mut mailbox, 2).await });
+
+    // Both reads must be in-flight at the same time to pass the barrier.
+    let barrier = Arc::new(Barrier::new(2));
+    let reads = futures_util::future::join(
+        addr.send(Read(barrier.clone())),
+        addr.send(Read(barrier.clone())),
+    );
+    let (a, b) = tokio::time::timeout(Duration::from_secs(5), reads)
+        .await
+        .expect("reads should be handled concurrently");
+    assert_eq!((a.unwrap(), b.unwrap()), (0, 0));
+
+    // Writes are handled exclusively, after all previous reads.
+    let barrier = Arc::new(Barrier::new(1));
+    let (a, _, b) = futures_util::future::join3(
+        addr.send(Read(barrier.clone())),
+        addr.send(Write),
+        addr.send(Read(barrier.clone())),
+    )
+    .await;
+    assert_eq!((a.unwrap(), b.unwrap()), (0, 1));
+}
+
+#[tokio::test]
+async fn test_dispatch_concurrent_envelope() {
+    let actor = Store::default();
+
+    let (env, response) =
+        Envelope::new_with_response(StoreMsg::from(Read(Arc::new(Barrier::new(1)))));
+    let Ok(handled) = env.dispatch_concurrent(&actor) else {
+        panic!("reads should be handled concurrently");
+    };
+    handled.await;
+    assert!(matches!(response.await, Ok(StoreMsgReturn::Read(0))));
+
+    // A message which needs exclusive access is returned back.
+    let (env, _response) = Envelope::new_with_response(StoreMsg::from(Write));
+    let Err(env) = env.dispatch_concurrent(&actor) else {
+        panic!("writes should not be handled concurrently");
+    };
+    assert!(env.wants_response());
+}
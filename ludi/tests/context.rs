@@ -0,0 +1,84 @@
+use ludi::{Actor, Address, Context, Handler, Runner};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = bool)]
+struct HasAddress;
+
+#[derive(ludi::Message)]
+struct Forward;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = usize)]
+struct Count;
+
+#[derive(ludi::Wrap)]
+enum EchoMsg {
+    HasAddress(HasAddress),
+    Forward(Forward),
+    Count(Count),
+}
+
+#[derive(Default)]
+struct Echo {
+    count: usize,
+}
+
+impl Actor for Echo {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<HasAddress> for Echo {
+    async fn handle(&mut self, _msg: HasAddress, ctx: &mut Context<Self>) -> bool {
+        ctx.address::<EchoMsg>().is_some()
+    }
+}
+
+impl Handler<Forward> for Echo {
+    async fn handle(&mut self, _msg: Forward, ctx: &mut Context<Self>) {
+        self.count += 1;
+
+        let addr: Address<EchoMsg> = ctx.address().unwrap();
+        tokio::spawn(async move { addr.send(Count).await });
+    }
+}
+
+impl Handler<Count> for Echo {
+    async fn handle(&mut self, _msg: Count, _ctx: &mut Context<Self>) -> usize {
+        self.count += 1;
+        self.count
+    }
+}
+
+#[tokio::test]
+async fn test_address() {
+    let (mut mailbox, addr) = ludi::mailbox::<EchoMsg>(8);
+    let mut actor = Echo::default();
+
+    let handle = tokio::spawn(async move {
+        Runner::new().run(&mut actor, &mut mailbox).await?;
+        Ok::<_, ()>(actor.count)
+    });
+
+    assert!(addr.send(HasAddress).await.unwrap());
+
+    addr.send(Forward).await.unwrap();
+
+    // The actor's own address does not keep the mailbox open.
+    drop(addr);
+    assert_eq!(handle.await.unwrap().unwrap(), 2);
+}
+
+#[tokio::test]
+async fn test_address_run() {
+    let (mut mailbox, addr) = ludi::mailbox::<EchoMsg>(8);
+    let mut actor = Echo::default();
+
+    tokio::spawn(async move { ludi::run(&mut actor, &mut mailbox).await });
+
+    assert!(!addr.send(HasAddress).await.unwrap());
+}
@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex};
+
+use futures_util::future::BoxFuture;
+use ludi::{Actor, Context, Error, Handler, Layer, Next, Runner};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Add(u32);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Get;
+
+#[derive(ludi::Wrap)]
+enum SumMsg {
+    Add(Add),
+    Get(Get),
+}
+
+#[derive(Default)]
+struct Sum(u32);
+
+impl Actor for Sum {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Add> for Sum {
+    async fn handle(&mut self, msg: Add, _ctx: &mut Context<Self>) -> u32 {
+        self.0 += msg.0;
+        self.0
+    }
+}
+
+impl Handler<Get> for Sum {
+    async fn handle(&mut self, _msg: Get, _ctx: &mut Context<Self>) -> u32 {
+        self.0
+    }
+}
+
+/// Records the label of every message.
+#[derive(Clone, Default)]
+struct Log(Arc<Mutex<Vec<String>>>);
+
+impl Layer<SumMsg> for Log {
+    fn dispatch<'a>(
+        &'a self,
+        msg: SumMsg,
+        next: Next<'a, SumMsg>,
+    ) -> BoxFuture<'a, Option<SumMsgReturn>> {
+        Box::pin(async move {
+            self.0
+                .lock()
+                .unwrap()
+                .push(ludi::Message::label(&msg).to_string());
+            next.run(msg).await
+        })
+    }
+}
+
+/// Rejects empty additions, and answers `Get` with a cached value.
+struct Filter;
+
+impl Layer<SumMsg> for Filter {
+    fn dispatch<'a>(
+        &'a self,
+        msg: SumMsg,
+        next: Next<'a, SumMsg>,
+    ) -> BoxFuture<'a, Option<SumMsgReturn>> {
+        Box::pin(async move {
+            match msg {
+                SumMsg::Add(Add(0)) => None,
+                SumMsg::Get(Get) => Some(SumMsgReturn::Get(42)),
+                msg => next.run(msg).await,
+            }
+        })
+    }
+}
+
+/// Doubles every sum which is returned.
+struct Double;
+
+impl Layer<SumMsg> for Double {
+    fn dispatch<'a>(
+        &'a self,
+        msg: SumMsg,
+        next: Next<'a, SumMsg>,
+    ) -> BoxFuture<'a, Option<SumMsgReturn>> {
+        Box::pin(async move {
+            match next.run(msg).await {
+                Some(SumMsgReturn::Add(sum)) => Some(SumMsgReturn::Add(sum * 2)),
+                ret => ret,
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_layers() {
+    let (mut mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let log = Log::default();
+
+    let runner = Runner::new()
+        .with_layer(log.clone())
+        .with_layer(Filter)
+        .with_layer(Double);
+    tokio::spawn(async move { runner.run(&mut Sum::default(), &mut mailbox).await });
+
+    assert_eq!(addr.send(Add(1)).await, Ok(2));
+    assert_eq!(addr.send(Add(0)).await, Err(Error::Interrupted));
+    assert_eq!(addr.send(Get).await, Ok(42));
+    assert_eq!(addr.send(Add(2)).await, Ok(6));
+
+    assert_eq!(*log.0.lock().unwrap(), vec!["Add", "Add", "Get", "Add"]);
+}
@@ -0,0 +1,60 @@
+use ludi::{Actor, Context, Handler};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Double(u32);
+
+struct Doubler;
+
+impl Actor for Doubler {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Double> for Doubler {
+    async fn handle(&mut self, msg: Double, _ctx: &mut Context<Self>) -> u32 {
+        msg.0 * 2
+    }
+}
+
+#[tokio::test]
+async fn test_is_connected() {
+    let (mut mailbox, addr) = ludi::mailbox::<Double>(8);
+    assert!(addr.is_connected());
+
+    mailbox.close();
+    assert!(!addr.is_connected());
+
+    drop(mailbox);
+    assert!(!addr.is_connected());
+}
+
+#[tokio::test]
+async fn test_closed() {
+    let (mut mailbox, addr) = ludi::mailbox::<Double>(8);
+    let closed = addr.closed();
+
+    tokio::spawn(async move { ludi::run(&mut Doubler, &mut mailbox).await });
+    assert_eq!(addr.send(Double(2)).await, Ok(4));
+
+    drop(addr);
+    closed.await;
+}
+
+#[tokio::test]
+async fn test_mailbox_len() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<Double>(8);
+    assert!(mailbox.is_empty());
+
+    addr.try_queue(Double(1)).unwrap();
+    addr.queue(Double(2)).await.unwrap();
+    assert_eq!(mailbox.len(), 2);
+
+    drop(addr);
+    ludi::run(&mut Doubler, &mut mailbox).await.unwrap();
+    assert!(mailbox.is_empty());
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use ludi::{metrics::Metrics, Actor, Context, Handler, Runner};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Add(u32);
+
+#[derive(ludi::Message)]
+struct Slow;
+
+#[derive(ludi::Wrap)]
+enum SumMsg {
+    Add(Add),
+    Slow(Slow),
+}
+
+#[derive(Default)]
+struct Sum(u32);
+
+impl Actor for Sum {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Add> for Sum {
+    async fn handle(&mut self, msg: Add, _ctx: &mut Context<Self>) -> u32 {
+        self.0 += msg.0;
+        self.0
+    }
+}
+
+impl Handler<Slow> for Sum {
+    async fn handle(&mut self, _msg: Slow, _ctx: &mut Context<Self>) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let (mut mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let metrics = Metrics::new();
+
+    addr.queue(Add(1).into()).await.unwrap();
+    addr.queue(Add(2).into()).await.unwrap();
+    addr.queue(Slow.into()).await.unwrap();
+    drop(addr);
+
+    Runner::new()
+        .with_metrics(metrics.clone())
+        .run(&mut Sum::default(), &mut mailbox)
+        .await
+        .unwrap();
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.processed(), 3);
+    assert_eq!(snapshot.max_queue_depth(), 2);
+    assert_eq!(snapshot.queue_depth(), 0);
+
+    let add = snapshot.message("Add").unwrap();
+    assert_eq!(add.count(), 2);
+    // The messages were queued before the runner started recording metrics.
+    assert_eq!(add.max_wait(), Duration::ZERO);
+
+    let slow = snapshot.message("Slow").unwrap();
+    assert_eq!(slow.count(), 1);
+    assert!(slow.max_duration() >= Duration::from_millis(10));
+
+    metrics.reset();
+    assert_eq!(metrics.snapshot().processed(), 0);
+}
+
+#[tokio::test]
+async fn test_metrics_wait() {
+    let (mut mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let metrics = Metrics::new();
+
+    let runner = Runner::new().with_metrics(metrics.clone());
+    let task = tokio::spawn(async move { runner.run(&mut Sum::default(), &mut mailbox).await });
+    assert_eq!(addr.send(Add(1)).await.unwrap(), 1);
+
+    // The second message waits for the slow message to be handled.
+    addr.queue(Slow.into()).await.unwrap();
+    assert_eq!(addr.send(Add(2)).await.unwrap(), 3);
+    drop(addr);
+    task.await.unwrap().unwrap();
+
+    let snapshot = metrics.snapshot();
+    let add = snapshot.message("Add").unwrap();
+    assert!(add.max_wait() >= Duration::from_millis(10));
+}
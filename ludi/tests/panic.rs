@@ -0,0 +1,138 @@
+use ludi::{Actor, ConcurrentHandler, Context, Error, Handler, Runner};
+
+#[derive(ludi::Message)]
+struct Panic;
+
+#[derive(ludi::Message)]
+#[ludi(concurrent)]
+struct PanicConcurrent;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = usize)]
+struct Count;
+
+#[derive(ludi::Wrap)]
+enum FragileMsg {
+    Panic(Panic),
+    PanicConcurrent(PanicConcurrent),
+    Count(Count),
+}
+
+#[derive(Default)]
+struct Fragile {
+    resume: bool,
+    count: usize,
+    panics: Vec<String>,
+}
+
+impl Actor for Fragile {
+    type Stop = Vec<String>;
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(std::mem::take(&mut self.panics))
+    }
+
+    fn panicked(&mut self, message: &str, ctx: &mut Context<Self>) {
+        self.panics.push(message.to_string());
+        if !self.resume {
+            ctx.stop();
+        }
+    }
+}
+
+impl Handler<Panic> for Fragile {
+    async fn handle(&mut self, _msg: Panic, _ctx: &mut Context<Self>) {
+        panic!("boom");
+    }
+}
+
+impl Handler<PanicConcurrent> for Fragile {
+    async fn handle(&mut self, msg: PanicConcurrent, _ctx: &mut Context<Self>) {
+        self.handle_concurrent(msg).await
+    }
+}
+
+impl ConcurrentHandler<PanicConcurrent> for Fragile {
+    async fn handle_concurrent(&self, _msg: PanicConcurrent) {
+        panic!("boom");
+    }
+}
+
+impl Handler<Count> for Fragile {
+    async fn handle(&mut self, _msg: Count, _ctx: &mut Context<Self>) -> usize {
+        self.count += 1;
+        self.count
+    }
+}
+
+#[tokio::test]
+async fn test_panic_resume() {
+    let (mut mailbox, addr) = ludi::mailbox::<FragileMsg>(8);
+    let mut actor = Fragile {
+        resume: true,
+        ..Default::default()
+    };
+
+    let handle = tokio::spawn(async move {
+        Runner::new()
+            .with_catch_panics(true)
+            .run(&mut actor, &mut mailbox)
+            .await
+    });
+
+    assert_eq!(
+        addr.send(Panic).await,
+        Err(Error::Panicked {
+            message: "boom".to_string()
+        })
+    );
+    assert_eq!(addr.send(Count).await, Ok(1));
+
+    drop(addr);
+    assert_eq!(handle.await.unwrap().unwrap(), vec!["boom".to_string()]);
+}
+
+#[tokio::test]
+async fn test_panic_stop() {
+    let (mut mailbox, addr) = ludi::mailbox::<FragileMsg>(8);
+    let mut actor = Fragile::default();
+
+    let handle = tokio::spawn(async move {
+        Runner::new()
+            .with_catch_panics(true)
+            .run(&mut actor, &mut mailbox)
+            .await
+    });
+
+    assert!(matches!(
+        addr.send(Panic).await,
+        Err(Error::Panicked { .. })
+    ));
+    assert_eq!(handle.await.unwrap().unwrap(), vec!["boom".to_string()]);
+}
+
+#[tokio::test]
+async fn test_panic_concurrent() {
+    let (mut mailbox, addr) = ludi::mailbox::<FragileMsg>(8);
+    let mut actor = Fragile {
+        resume: true,
+        ..Default::default()
+    };
+
+    let handle = tokio::spawn(async move {
+        Runner::new()
+            .with_catch_panics(true)
+            .run_concurrent(&mut actor, &mut mailbox, 2)
+            .await
+    });
+
+    assert!(matches!(
+        addr.send(PanicConcurrent).await,
+        Err(Error::Panicked { .. })
+    ));
+    assert_eq!(addr.send(Count).await, Ok(1));
+
+    drop(addr);
+    assert_eq!(handle.await.unwrap().unwrap(), vec!["boom".to_string()]);
+}
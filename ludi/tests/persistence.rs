@@ -0,0 +1,211 @@
+use std::num::NonZeroU64;
+
+use ludi::{
+    persistence::{EventSourced, FileJournal, InMemoryJournal, Journal, JournalError, Recovery},
+    Actor, Context, Handler,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u64)]
+struct Add(u64);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Added(u64);
+
+struct Counter<J> {
+    total: u64,
+    /// The number of events applied since the actor was created.
+    applied: usize,
+    journal: J,
+    snapshot_interval: Option<NonZeroU64>,
+}
+
+impl<J> Counter<J> {
+    fn new(journal: J) -> Self {
+        Self {
+            total: 0,
+            applied: 0,
+            journal,
+            snapshot_interval: None,
+        }
+    }
+}
+
+impl<J> Actor for Counter<J>
+where
+    J: Journal<Event = Added, Snapshot = u64>,
+{
+    type Stop = (u64, usize);
+    type Error = JournalError;
+
+    fn started(&mut self, _ctx: &mut Context<Self>) -> Result<(), Self::Error> {
+        self.recover()
+    }
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok((self.total, self.applied))
+    }
+}
+
+impl<J> EventSourced for Counter<J>
+where
+    J: Journal<Event = Added, Snapshot = u64>,
+{
+    type Event = Added;
+    type Snapshot = u64;
+    type Journal = J;
+
+    fn journal(&mut self) -> &mut J {
+        &mut self.journal
+    }
+
+    fn apply(&mut self, event: &Added) {
+        self.total += event.0;
+        self.applied += 1;
+    }
+
+    fn snapshot(&self) -> u64 {
+        self.total
+    }
+
+    fn restore(&mut self, snapshot: u64) {
+        self.total = snapshot;
+    }
+
+    fn snapshot_interval(&self) -> Option<NonZeroU64> {
+        self.snapshot_interval
+    }
+}
+
+impl<J> Handler<Add> for Counter<J>
+where
+    J: Journal<Event = Added, Snapshot = u64>,
+{
+    async fn handle(&mut self, msg: Add, _ctx: &mut Context<Self>) -> u64 {
+        self.persist(Added(msg.0)).await.unwrap();
+        self.total
+    }
+}
+
+/// A journal which fails to save snapshots.
+struct NoSnapshots(InMemoryJournal<Added, u64>);
+
+impl Journal for NoSnapshots {
+    type Event = Added;
+    type Snapshot = u64;
+
+    async fn append(&mut self, event: &Added) -> Result<u64, JournalError> {
+        self.0.append(event).await
+    }
+
+    async fn save_snapshot(&mut self, _seq: u64, _snapshot: &u64) -> Result<(), JournalError> {
+        Err(std::io::Error::other("disk full").into())
+    }
+
+    fn load(&mut self) -> Result<Recovery<Added, u64>, JournalError> {
+        self.0.load()
+    }
+}
+
+/// Runs a counter, adds each value and returns the total and the number of applied events.
+async fn run<J>(mut counter: Counter<J>, values: &[u64]) -> (u64, usize)
+where
+    J: Journal<Event = Added, Snapshot = u64> + 'static,
+{
+    let (mut mailbox, addr) = ludi::mailbox::<Add>(8);
+    let handle = tokio::spawn(async move { ludi::run(&mut counter, &mut mailbox).await });
+
+    for value in values {
+        addr.send(Add(*value)).await.unwrap();
+    }
+    drop(addr);
+
+    handle.await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn test_event_sourced_recover() {
+    let journal = InMemoryJournal::new();
+
+    assert_eq!(run(Counter::new(journal.clone()), &[1, 2, 3]).await, (6, 3));
+    assert_eq!(journal.len(), 3);
+
+    // The restarted actor replays the journal before handling messages.
+    assert_eq!(run(Counter::new(journal.clone()), &[4]).await, (10, 4));
+    assert_eq!(journal.len(), 4);
+}
+
+#[tokio::test]
+async fn test_event_sourced_snapshot() {
+    let journal = InMemoryJournal::new();
+
+    let mut counter = Counter::new(journal.clone());
+    counter.snapshot_interval = NonZeroU64::new(3);
+    assert_eq!(run(counter, &[1, 2, 3, 4, 5, 6, 7]).await, (28, 7));
+    assert_eq!(journal.snapshot_seq(), Some(6));
+
+    // Only the event after the snapshot is replayed.
+    assert_eq!(run(Counter::new(journal.clone()), &[]).await, (28, 1));
+}
+
+#[tokio::test]
+async fn test_event_sourced_snapshot_failed() {
+    let journal = InMemoryJournal::new();
+
+    // The events are persisted even though the snapshots fail.
+    let mut counter = Counter::new(NoSnapshots(journal.clone()));
+    counter.snapshot_interval = NonZeroU64::new(2);
+    assert_eq!(run(counter, &[1, 2, 3]).await, (6, 3));
+    assert_eq!(journal.len(), 3);
+    assert_eq!(journal.snapshot_seq(), None);
+}
+
+#[tokio::test]
+async fn test_file_journal() {
+    let path = std::env::temp_dir().join(format!("ludi-journal-{}", std::process::id()));
+    let snapshot_path = path.with_file_name(format!(
+        "{}.snapshot",
+        path.file_name().unwrap().to_str().unwrap()
+    ));
+
+    let mut counter = Counter::new(FileJournal::open(&path).unwrap());
+    counter.snapshot_interval = NonZeroU64::new(2);
+    assert_eq!(run(counter, &[1, 2, 3]).await, (6, 3));
+
+    let journal = FileJournal::<Added, u64>::open(&path).unwrap();
+    assert_eq!(journal.len(), 3);
+    assert_eq!(run(Counter::new(journal), &[4]).await, (10, 2));
+
+    std::fs::remove_file(&path).unwrap();
+    std::fs::remove_file(&snapshot_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_file_journal_torn_write() {
+    let path = std::env::temp_dir().join(format!("ludi-journal-torn-{}", std::process::id()));
+
+    // The last event was only partially written.
+    std::fs::write(&path, "1\n2\n3").unwrap();
+
+    let journal = FileJournal::<Added, u64>::open(&path).unwrap();
+    assert_eq!(journal.len(), 2);
+    assert_eq!(run(Counter::new(journal), &[4]).await, (7, 3));
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n2\n4\n");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn test_file_journal_dropped_append() {
+    let path = std::env::temp_dir().join(format!("ludi-journal-dropped-{}", std::process::id()));
+
+    let mut journal = FileJournal::<Added, u64>::open(&path).unwrap();
+    // The event is still written, and counted, once its future is dropped.
+    drop(journal.append(&Added(1)));
+    assert_eq!(journal.append(&Added(2)).await.unwrap(), 2);
+    assert_eq!(journal.len(), 2);
+
+    std::fs::remove_file(&path).unwrap();
+}
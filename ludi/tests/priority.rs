@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use futures_util::SinkExt;
+use ludi::{Actor, Context, Envelope, Handler, Priority, PriorityPolicy};
+
+#[derive(ludi::Message)]
+struct Record(&'static str);
+
+#[derive(Default)]
+struct Recorder {
+    order: Vec<&'static str>,
+}
+
+impl Actor for Recorder {
+    type Stop = Vec<&'static str>;
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(std::mem::take(&mut self.order))
+    }
+}
+
+impl Handler<Record> for Recorder {
+    async fn handle(&mut self, msg: Record, _ctx: &mut Context<Self>) {
+        self.order.push(msg.0);
+    }
+}
+
+/// Queues bulk messages followed by urgent messages, then returns the order in which they
+/// were handled.
+async fn handled_order(policy: PriorityPolicy) -> Vec<&'static str> {
+    let (mut mailbox, addr) = ludi::priority_mailbox_with_policy::<Record>(8, 2, policy);
+    let mut actor = Recorder::default();
+
+    for id in ["low-1", "low-2", "low-3"] {
+        addr.queue_with_priority(Record(id), Priority::LOW)
+            .await
+            .unwrap();
+    }
+    for id in ["high-1", "high-2"] {
+        addr.queue_with_priority(Record(id), Priority::HIGH)
+            .await
+            .unwrap();
+    }
+    drop(addr);
+
+    ludi::run(&mut actor, &mut mailbox).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_priority_strict() {
+    assert_eq!(
+        handled_order(PriorityPolicy::Strict).await,
+        ["high-1", "high-2", "low-1", "low-2", "low-3"]
+    );
+}
+
+#[tokio::test]
+async fn test_priority_max_burst() {
+    assert_eq!(
+        handled_order(PriorityPolicy::MaxBurst(1)).await,
+        ["high-1", "low-1", "high-2", "low-2", "low-3"]
+    );
+}
+
+#[tokio::test]
+async fn test_priority_send() {
+    let (mut mailbox, addr) = ludi::priority_mailbox::<Record>(8, 3);
+    let mut actor = Recorder::default();
+
+    tokio::spawn(async move { ludi::run(&mut actor, &mut mailbox).await });
+
+    addr.send_with_priority(Record("urgent"), Priority::HIGH)
+        .await
+        .unwrap();
+    addr.send(Record("normal")).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_priority_sink() {
+    let (mut mailbox, mut addr) = ludi::priority_mailbox::<Record>(1, 2);
+
+    // Fill the low lane, including the slot which is guaranteed to the address.
+    for id in ["low-1", "low-2"] {
+        SinkExt::send(
+            &mut addr,
+            Envelope::new(Record(id)).with_priority(Priority::LOW),
+        )
+        .await
+        .unwrap();
+    }
+    // A full low lane does not hold up an urgent message.
+    let urgent = Envelope::new(Record("high")).with_priority(Priority::HIGH);
+    tokio::time::timeout(Duration::from_millis(100), SinkExt::send(&mut addr, urgent))
+        .await
+        .expect("the high lane is not full")
+        .unwrap();
+    drop(addr);
+
+    assert_eq!(
+        ludi::run(&mut Recorder::default(), &mut mailbox)
+            .await
+            .unwrap(),
+        ["high", "low-1", "low-2"]
+    );
+}
@@ -0,0 +1,93 @@
+use ludi::{Actor, Broker, Context, Error, Handler, Recipient};
+
+#[derive(Clone, ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Ping(u32);
+
+#[derive(ludi::Message)]
+struct Stop;
+
+#[derive(ludi::Wrap)]
+enum EchoMsg {
+    Ping(Ping),
+    Stop(Stop),
+}
+
+struct Echo;
+
+impl Actor for Echo {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Ping> for Echo {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        msg.0
+    }
+}
+
+impl Handler<Stop> for Echo {
+    async fn handle(&mut self, _msg: Stop, ctx: &mut Context<Self>) {
+        ctx.stop();
+    }
+}
+
+struct Double;
+
+impl Actor for Double {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Ping> for Double {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        msg.0 * 2
+    }
+}
+
+#[tokio::test]
+async fn test_recipients() {
+    let (mut echo_mailbox, echo_addr) = ludi::mailbox::<EchoMsg>(8);
+    let (mut double_mailbox, double_addr) = ludi::mailbox::<Ping>(8);
+    tokio::spawn(async move { ludi::run(&mut Echo, &mut echo_mailbox).await });
+    tokio::spawn(async move { ludi::run(&mut Double, &mut double_mailbox).await });
+
+    let recipients: Vec<Recipient<Ping>> = vec![echo_addr.recipient(), double_addr.into()];
+
+    let mut responses = Vec::new();
+    for recipient in &recipients {
+        responses.push(recipient.send(Ping(2)).await.unwrap());
+    }
+    assert_eq!(responses, vec![2, 4]);
+
+    echo_addr.send(Stop).await.unwrap();
+    drop(echo_addr);
+
+    recipients[0].closed().await;
+    assert!(!recipients[0].is_connected());
+    assert_eq!(recipients[0].send(Ping(1)).await, Err(Error::Disconnected));
+    assert!(recipients[1].is_connected());
+}
+
+#[tokio::test]
+async fn test_recipient_subscriber() {
+    let (mut mailbox, addr) = ludi::mailbox::<Ping>(8);
+    let broker = Broker::new();
+
+    broker.subscribe(addr.recipient::<Ping>());
+    assert_eq!(broker.publish(Ping(1)).await, 1);
+
+    // The subscription keeps the mailbox open.
+    drop(addr);
+    drop(broker);
+    assert_eq!(mailbox.len(), 1);
+    ludi::run(&mut Double, &mut mailbox).await.unwrap();
+}
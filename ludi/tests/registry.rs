@@ -0,0 +1,129 @@
+use futures_util::StreamExt;
+use ludi::{Actor, Context, Handler, Registry};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Ping(u32);
+
+#[derive(ludi::Message)]
+struct Stop;
+
+#[derive(ludi::Wrap)]
+enum EchoMsg {
+    Ping(Ping),
+    Stop(Stop),
+}
+
+struct Echo(u32);
+
+impl Actor for Echo {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Ping> for Echo {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        msg.0 + self.0
+    }
+}
+
+impl Handler<Stop> for Echo {
+    async fn handle(&mut self, _msg: Stop, ctx: &mut Context<Self>) {
+        ctx.stop();
+    }
+}
+
+/// A marker type to register the echo actor under.
+struct EchoKey;
+
+fn spawn_echo(offset: u32) -> (ludi::Address<EchoMsg>, tokio::task::JoinHandle<()>) {
+    let (mut mailbox, addr) = ludi::mailbox::<EchoMsg>(8);
+    let handle = tokio::spawn(async move {
+        ludi::run(&mut Echo(offset), &mut mailbox).await.unwrap();
+    });
+    (addr, handle)
+}
+
+#[tokio::test]
+async fn test_registry_lookup() {
+    let registry = Registry::new();
+    let (addr, _) = spawn_echo(0);
+
+    registry.register("echo", &addr);
+    registry.register_type::<EchoKey, _>(&addr);
+    assert_eq!(registry.len(), 2);
+
+    let echo = registry
+        .lookup::<EchoMsg>("echo")
+        .unwrap()
+        .upgrade()
+        .unwrap();
+    assert_eq!(echo.send(Ping(1)).await.unwrap(), 1);
+
+    let echo = registry.lookup_type::<EchoKey, EchoMsg>().unwrap();
+    assert_eq!(echo.upgrade().unwrap().send(Ping(2)).await.unwrap(), 2);
+
+    let recipient = registry.recipient::<EchoMsg, Ping>("echo").unwrap();
+    assert_eq!(recipient.send(Ping(3)).await.unwrap(), 3);
+
+    // Lookups with the wrong name or message type find nothing.
+    assert!(registry.lookup::<EchoMsg>("other").is_none());
+    assert!(registry.lookup::<Ping>("echo").is_none());
+
+    assert!(registry.unregister("echo"));
+    assert!(!registry.unregister("echo"));
+    assert!(registry.lookup::<EchoMsg>("echo").is_none());
+    assert!(registry.unregister_type::<EchoKey>());
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn test_registry_removes_closed() {
+    let registry = Registry::new();
+    let (addr, handle) = spawn_echo(0);
+
+    registry.register("echo", &addr);
+    addr.send(Stop).await.unwrap();
+    handle.await.unwrap();
+
+    assert!(registry.lookup::<EchoMsg>("echo").is_none());
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn test_registry_does_not_keep_actor_alive() {
+    let registry = Registry::new();
+    let (addr, handle) = spawn_echo(0);
+
+    registry.register("echo", &addr);
+    drop(addr);
+    handle.await.unwrap();
+
+    assert!(registry.recipient::<EchoMsg, Ping>("echo").is_none());
+}
+
+#[tokio::test]
+async fn test_registry_watch() {
+    let registry = Registry::new();
+    let (addr, handle) = spawn_echo(0);
+    registry.register("echo", &addr);
+
+    let mut watch = registry.watch::<EchoMsg>("echo");
+
+    // The current registration is yielded first.
+    let echo = watch.next().await.unwrap().upgrade().unwrap();
+    assert_eq!(echo.send(Ping(1)).await.unwrap(), 1);
+
+    // Restart the actor and register it again.
+    echo.send(Stop).await.unwrap();
+    handle.await.unwrap();
+    let (addr, _) = spawn_echo(10);
+    registry.register("echo", &addr);
+
+    let echo = watch.next().await.unwrap().upgrade().unwrap();
+    assert_eq!(echo.send(Ping(1)).await.unwrap(), 11);
+}
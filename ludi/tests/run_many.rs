@@ -0,0 +1,146 @@
+use ludi::{Actor, Context, Error, Handler, MailboxSet, Runner, SelectPolicy};
+
+#[derive(ludi::Message)]
+struct Pause;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Write(u32);
+
+/// Returns whether the actor has an address to each of its mailboxes.
+#[derive(ludi::Message)]
+#[ludi(return_ty = bool)]
+struct Addresses;
+
+#[derive(ludi::Message)]
+struct Crash;
+
+#[derive(ludi::Wrap)]
+enum ControlMsg {
+    Pause(Pause),
+    Addresses(Addresses),
+    Crash(Crash),
+}
+
+#[derive(ludi::Wrap)]
+enum DataMsg {
+    Write(Write),
+}
+
+#[derive(Default)]
+struct Writer {
+    log: Vec<&'static str>,
+}
+
+impl Actor for Writer {
+    type Stop = Vec<&'static str>;
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(std::mem::take(&mut self.log))
+    }
+}
+
+impl Handler<Pause> for Writer {
+    async fn handle(&mut self, _msg: Pause, _ctx: &mut Context<Self>) {
+        self.log.push("pause");
+    }
+}
+
+impl Handler<Addresses> for Writer {
+    async fn handle(&mut self, _msg: Addresses, ctx: &mut Context<Self>) -> bool {
+        ctx.address::<ControlMsg>().is_some() && ctx.address::<DataMsg>().is_some()
+    }
+}
+
+impl Handler<Crash> for Writer {
+    async fn handle(&mut self, _msg: Crash, _ctx: &mut Context<Self>) {
+        panic!("crash");
+    }
+}
+
+impl Handler<Write> for Writer {
+    async fn handle(&mut self, msg: Write, _ctx: &mut Context<Self>) -> u32 {
+        self.log.push("write");
+        msg.0
+    }
+}
+
+/// Queues messages in both mailboxes, then runs the actor until they are closed.
+async fn run(policy: SelectPolicy) -> Vec<&'static str> {
+    let (control_mailbox, mut control) = ludi::mailbox::<ControlMsg>(8);
+    let (data_mailbox, mut data) = ludi::mailbox::<DataMsg>(8);
+
+    for i in 0..3 {
+        data.try_queue(Write(i).into()).unwrap();
+    }
+    for _ in 0..2 {
+        control.try_queue(Pause.into()).unwrap();
+    }
+    drop((control, data));
+
+    let mut mailboxes = MailboxSet::with_policy(policy)
+        .with(control_mailbox)
+        .with(data_mailbox);
+
+    ludi::run_many(&mut Writer::default(), &mut mailboxes)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_run_many_biased() {
+    assert_eq!(
+        run(SelectPolicy::Biased).await,
+        vec!["pause", "pause", "write", "write", "write"]
+    );
+}
+
+#[tokio::test]
+async fn test_run_many_round_robin() {
+    assert_eq!(
+        run(SelectPolicy::RoundRobin).await,
+        vec!["pause", "write", "pause", "write", "write"]
+    );
+}
+
+#[tokio::test]
+async fn test_run_many_until_closed() {
+    let (control_mailbox, control) = ludi::mailbox::<ControlMsg>(8);
+    let (data_mailbox, data) = ludi::mailbox::<DataMsg>(8);
+
+    let mut mailboxes = MailboxSet::new().with(control_mailbox).with(data_mailbox);
+    let handle =
+        tokio::spawn(async move { ludi::run_many(&mut Writer::default(), &mut mailboxes).await });
+
+    // The actor keeps running while any of its mailboxes is open.
+    drop(control);
+    assert_eq!(data.send(Write(1)).await, Ok(1));
+
+    drop(data);
+    assert_eq!(handle.await.unwrap(), Ok(vec!["write"]));
+}
+
+#[tokio::test]
+async fn test_runner_run_many() {
+    let (control_mailbox, control) = ludi::mailbox::<ControlMsg>(8);
+    let (data_mailbox, data) = ludi::mailbox::<DataMsg>(8);
+
+    let mut mailboxes = MailboxSet::new().with(control_mailbox).with(data_mailbox);
+    let handle = tokio::spawn(async move {
+        Runner::new()
+            .with_catch_panics(true)
+            .run_many(&mut Writer::default(), &mut mailboxes)
+            .await
+    });
+
+    assert_eq!(control.send(Addresses).await, Ok(true));
+    assert_eq!(data.send(Write(1)).await, Ok(1));
+
+    // The panic is caught, and the actor is stopped by default.
+    assert!(matches!(
+        control.send(Crash).await,
+        Err(Error::Panicked { .. })
+    ));
+    assert_eq!(handle.await.unwrap(), Ok(vec!["write"]));
+}
@@ -0,0 +1,115 @@
+use ludi::{
+    spawner::{Spawner, Task, TokioSpawner},
+    Actor, Context, Error, Handler, JoinError,
+};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Add(u32);
+
+#[derive(ludi::Message)]
+struct Stop;
+
+#[derive(ludi::Message)]
+struct Panic;
+
+#[derive(ludi::Wrap)]
+enum SumMsg {
+    Add(Add),
+    Stop(Stop),
+    Panic(Panic),
+}
+
+#[derive(Default)]
+struct Sum(u32);
+
+impl Actor for Sum {
+    type Stop = u32;
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+impl Handler<Add> for Sum {
+    async fn handle(&mut self, msg: Add, _ctx: &mut Context<Self>) -> u32 {
+        self.0 += msg.0;
+        self.0
+    }
+}
+
+impl Handler<Stop> for Sum {
+    async fn handle(&mut self, _msg: Stop, ctx: &mut Context<Self>) {
+        ctx.stop();
+    }
+}
+
+impl Handler<Panic> for Sum {
+    async fn handle(&mut self, _msg: Panic, _ctx: &mut Context<Self>) {
+        panic!("boom");
+    }
+}
+
+#[tokio::test]
+async fn test_spawn_join() {
+    let (mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let handle = ludi::spawn(Sum::default(), mailbox, &TokioSpawner);
+
+    assert_eq!(handle.address().unwrap().send(Add(1)).await, Ok(1));
+    assert_eq!(addr.send(Add(2)).await, Ok(3));
+
+    addr.send(Stop).await.unwrap();
+    assert_eq!(handle.join().await.unwrap(), Ok(3));
+}
+
+#[tokio::test]
+async fn test_spawn_handle_does_not_keep_actor_running() {
+    let (mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let handle = ludi::spawn(Sum::default(), mailbox, &TokioSpawner);
+
+    addr.send(Add(1)).await.unwrap();
+    drop(addr);
+
+    assert!(handle.address().is_none());
+    assert_eq!(handle.join().await.unwrap(), Ok(1));
+}
+
+#[tokio::test]
+async fn test_spawn_abort() {
+    let (mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let handle = ludi::spawn(Sum::default(), mailbox, &TokioSpawner);
+
+    let closed = addr.closed();
+    handle.abort();
+    closed.await;
+
+    assert_eq!(addr.send(Add(1)).await, Err(Error::Disconnected));
+}
+
+#[tokio::test]
+async fn test_spawn_join_panic() {
+    let (mailbox, addr) = ludi::mailbox::<SumMsg>(8);
+    let handle = ludi::spawn(Sum::default(), mailbox, &TokioSpawner);
+
+    let _ = addr.send(Panic).await;
+    match handle.join().await {
+        Err(JoinError::Panicked(payload)) => assert_eq!(payload.downcast_ref(), Some(&"boom")),
+        _ => panic!("expected the actor to panic"),
+    }
+}
+
+/// A spawner which drops every task without running it.
+struct DropSpawner;
+
+impl Spawner for DropSpawner {
+    fn spawn(&self, _task: Task) {}
+}
+
+#[tokio::test]
+async fn test_spawn_join_aborted() {
+    let (mailbox, _addr) = ludi::mailbox::<SumMsg>(8);
+    let handle = ludi::spawn(Sum::default(), mailbox, &DropSpawner);
+
+    assert!(matches!(handle.join().await, Err(JoinError::Aborted)));
+}
@@ -0,0 +1,108 @@
+use futures_util::SinkExt;
+use ludi::{
+    futures::ResponseFuture, Actor, Address, Context, Envelope, Error, Handler, Runner, StopMode,
+};
+
+#[derive(ludi::Message)]
+struct Stop(StopMode);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = usize)]
+struct Incr;
+
+#[derive(ludi::Wrap)]
+enum CounterMsg {
+    Stop(Stop),
+    Incr(Incr),
+}
+
+#[derive(Default)]
+struct Counter {
+    count: usize,
+}
+
+impl Actor for Counter {
+    type Stop = usize;
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(self.count)
+    }
+}
+
+impl Handler<Stop> for Counter {
+    async fn handle(&mut self, msg: Stop, ctx: &mut Context<Self>) {
+        ctx.stop_with(msg.0);
+    }
+}
+
+impl Handler<Incr> for Counter {
+    async fn handle(&mut self, _msg: Incr, _ctx: &mut Context<Self>) -> usize {
+        self.count += 1;
+        self.count
+    }
+}
+
+/// Queues a message without waiting for it to be handled.
+async fn queue(addr: &Address<CounterMsg>, msg: CounterMsg) -> ResponseFuture<CounterMsg> {
+    let (env, response) = Envelope::new_with_response(msg);
+    SinkExt::send(&mut addr.clone(), env).await.unwrap();
+    response
+}
+
+/// Stops the actor with the given mode while two messages are still queued.
+async fn stop_with_queued(
+    mode: StopMode,
+) -> (
+    usize,
+    Address<CounterMsg>,
+    ResponseFuture<CounterMsg>,
+    ResponseFuture<CounterMsg>,
+) {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    let mut actor = Counter::default();
+
+    addr.queue(Stop(mode).into()).await.unwrap();
+    let first = queue(&addr, Incr.into()).await;
+    let second = queue(&addr, Incr.into()).await;
+
+    let count = Runner::new().run(&mut actor, &mut mailbox).await.unwrap();
+
+    (count, addr, first, second)
+}
+
+#[tokio::test]
+async fn test_stop_drain() {
+    let (count, addr, first, second) = stop_with_queued(StopMode::Drain).await;
+
+    assert_eq!(count, 2);
+    assert!(first.await.is_ok());
+    assert!(second.await.is_ok());
+    assert!(matches!(addr.send(Incr).await, Err(Error::Stopped)));
+}
+
+#[tokio::test]
+async fn test_stop_reject() {
+    let (count, addr, first, second) = stop_with_queued(StopMode::Reject).await;
+
+    assert_eq!(count, 0);
+    assert!(matches!(first.await, Err(Error::Stopped)));
+    assert!(matches!(second.await, Err(Error::Stopped)));
+    assert!(matches!(addr.send(Incr).await, Err(Error::Stopped)));
+}
+
+#[tokio::test]
+async fn test_stop_drop() {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    let mut actor = Counter::default();
+
+    addr.queue(Stop(StopMode::Drop).into()).await.unwrap();
+    let first = queue(&addr, Incr.into()).await;
+
+    let count = Runner::new().run(&mut actor, &mut mailbox).await.unwrap();
+    assert_eq!(count, 0);
+
+    // Queued messages are rejected once the mailbox is dropped.
+    drop(mailbox);
+    assert!(matches!(first.await, Err(Error::Stopped)));
+}
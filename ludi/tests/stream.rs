@@ -0,0 +1,220 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use futures_util::{future::BoxFuture, stream, FutureExt, Stream, StreamExt};
+use ludi::{
+    Actor, Context, Error, Handler, Layer, Message, Next, Runner, StreamSender, StreamingHandler,
+};
+use tokio::sync::oneshot;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32, stream)]
+struct Count(u32);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32, stream)]
+struct Endless(oneshot::Sender<Error>);
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Ping(u32);
+
+#[derive(ludi::Wrap)]
+enum CounterMsg {
+    Count(Count),
+    Endless(Endless),
+    Ping(Ping),
+}
+
+struct Counter;
+
+impl Actor for Counter {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl StreamingHandler<Count> for Counter {
+    async fn handle_stream(
+        &mut self,
+        msg: Count,
+        items: StreamSender<u32>,
+        _ctx: &mut Context<Self>,
+    ) {
+        items.forward(stream::iter(0..msg.0)).await.unwrap();
+    }
+}
+
+impl StreamingHandler<Endless> for Counter {
+    async fn handle_stream(
+        &mut self,
+        msg: Endless,
+        mut items: StreamSender<u32>,
+        _ctx: &mut Context<Self>,
+    ) {
+        let mut i = 0;
+        let err = loop {
+            if let Err(err) = items.send(i).await {
+                break err;
+            }
+            i += 1;
+        };
+        assert!(items.is_closed());
+        msg.0.send(err).unwrap();
+    }
+}
+
+impl Handler<Ping> for Counter {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        msg.0
+    }
+}
+
+#[tokio::test]
+async fn test_send_stream() {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Counter, &mut mailbox).await });
+
+    // More items than fit in the stream's buffer.
+    let items: Vec<_> = addr.send_stream(Count(100)).collect().await;
+    assert_eq!(items, (0..100).map(Ok).collect::<Vec<_>>());
+
+    // The actor handles other messages once the stream is done.
+    assert_eq!(addr.send(Ping(1)).await, Ok(1));
+}
+
+/// A layer which counts the responses which pass through it.
+#[derive(Clone, Default)]
+struct Responses(Arc<AtomicUsize>);
+
+impl Layer<CounterMsg> for Responses {
+    fn dispatch<'a>(
+        &'a self,
+        msg: CounterMsg,
+        next: Next<'a, CounterMsg>,
+    ) -> BoxFuture<'a, Option<<CounterMsg as Message>::Return>> {
+        async move {
+            let ret = next.run(msg).await;
+            self.0.fetch_add(1, Ordering::Relaxed);
+            ret
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn test_send_stream_layer() {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    let responses = Responses::default();
+
+    let runner = Runner::new().with_layer(responses.clone());
+    tokio::spawn(async move { runner.run(&mut Counter, &mut mailbox).await });
+
+    // The stream passes through the layer before the handler has sent every item.
+    let items: Vec<_> = addr.send_stream(Count(100)).collect().await;
+    assert_eq!(items, (0..100).map(Ok).collect::<Vec<_>>());
+    assert_eq!(responses.0.load(Ordering::Relaxed), 1);
+
+    assert_eq!(addr.send(Ping(1)).await, Ok(1));
+    assert_eq!(responses.0.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn test_send_stream_dropped() {
+    let (mut mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Counter, &mut mailbox).await });
+
+    let (tx, rx) = oneshot::channel();
+    let items: Vec<_> = addr.send_stream(Endless(tx)).take(3).collect().await;
+    assert_eq!(items, vec![Ok(0), Ok(1), Ok(2)]);
+
+    // Dropping the stream disconnects the handler.
+    assert_eq!(rx.await.unwrap(), Error::Disconnected);
+    assert_eq!(addr.send(Ping(1)).await, Ok(1));
+}
+
+#[tokio::test]
+async fn test_send_stream_disconnected() {
+    let (mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    drop(mailbox);
+
+    let items: Vec<_> = addr.send_stream(Count(3)).collect().await;
+    assert_eq!(items, vec![Err(Error::Disconnected)]);
+}
+
+#[derive(Default, ludi::Controller)]
+pub struct Store {
+    values: Vec<u32>,
+}
+
+impl Actor for Store {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+#[ludi::implement]
+#[ctrl]
+#[msg(wrap)]
+impl Store {
+    pub fn scan(&self, from: u32) -> impl Stream<Item = u32> {
+        stream::iter(
+            self.values
+                .clone()
+                .into_iter()
+                .filter(move |value| *value >= from),
+        )
+    }
+
+    #[ctrl(err)]
+    pub fn try_scan(&self, from: u32) -> impl Stream<Item = Result<u32, Error>> {
+        stream::iter(self.values.clone())
+            .filter(move |value| std::future::ready(*value >= from))
+            .map(Ok)
+    }
+
+    pub async fn push(&mut self, value: u32) {
+        self.values.push(value);
+    }
+}
+
+#[tokio::test]
+async fn test_ctrl_stream() {
+    let (mut mailbox, addr) = ludi::mailbox::<StoreMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Store::default(), &mut mailbox).await });
+
+    let ctrl = StoreCtrl::from(addr);
+    for value in [3, 1, 4, 1, 5] {
+        ctrl.push(value).await;
+    }
+
+    let items: Vec<_> = ctrl.scan(3).collect().await;
+    assert_eq!(items, vec![3, 4, 5]);
+}
+
+#[tokio::test]
+async fn test_ctrl_stream_err() {
+    let (mut mailbox, addr) = ludi::mailbox::<StoreMsg>(8);
+    let ctrl = StoreCtrl::from(addr);
+    let task = tokio::spawn(async move { ludi::run(&mut Store::default(), &mut mailbox).await });
+
+    for value in [3, 1, 4] {
+        ctrl.push(value).await;
+    }
+    let items: Vec<_> = ctrl.try_scan(2).collect().await;
+    assert_eq!(items, vec![Ok(3), Ok(4)]);
+
+    // The error is yielded as an item instead of panicking.
+    task.abort();
+    let _ = task.await;
+    let items: Vec<_> = ctrl.try_scan(2).collect().await;
+    assert_eq!(items, vec![Err(Error::Disconnected)]);
+}
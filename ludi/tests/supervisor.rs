@@ -0,0 +1,185 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ludi::{Actor, Context, Handler, RestartIntensity, Runner, Strategy, Supervisor};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = usize)]
+struct Increment;
+
+#[derive(ludi::Message)]
+struct Fail;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = bool)]
+struct HasAddress;
+
+#[derive(ludi::Wrap)]
+enum CounterMsg {
+    Increment(Increment),
+    Fail(Fail),
+    HasAddress(HasAddress),
+}
+
+struct Counter {
+    count: usize,
+}
+
+impl Actor for Counter {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Increment> for Counter {
+    async fn handle(&mut self, _msg: Increment, _ctx: &mut Context<Self>) -> usize {
+        self.count += 1;
+        self.count
+    }
+}
+
+impl Handler<Fail> for Counter {
+    async fn handle(&mut self, _msg: Fail, ctx: &mut Context<Self>) {
+        ctx.set_error(());
+    }
+}
+
+impl Handler<HasAddress> for Counter {
+    async fn handle(&mut self, _msg: HasAddress, ctx: &mut Context<Self>) -> bool {
+        ctx.address::<CounterMsg>().is_some()
+    }
+}
+
+fn counter(starts: &Arc<AtomicUsize>) -> impl FnMut() -> Counter + Send + 'static {
+    let starts = starts.clone();
+    move || {
+        starts.fetch_add(1, Ordering::SeqCst);
+        Counter { count: 0 }
+    }
+}
+
+#[tokio::test]
+async fn test_one_for_one() {
+    let (mailbox_a, addr_a) = ludi::mailbox::<CounterMsg>(8);
+    let (mailbox_b, addr_b) = ludi::mailbox::<CounterMsg>(8);
+    let starts_a = Arc::new(AtomicUsize::new(0));
+    let starts_b = Arc::new(AtomicUsize::new(0));
+
+    let mut supervisor = Supervisor::new(Strategy::OneForOne);
+    supervisor
+        .add(counter(&starts_a), mailbox_a)
+        .add(counter(&starts_b), mailbox_b);
+    let handle = tokio::spawn(supervisor.run());
+
+    assert_eq!(addr_a.send(Increment).await.unwrap(), 1);
+    assert_eq!(addr_b.send(Increment).await.unwrap(), 1);
+
+    addr_a.send(Fail).await.unwrap();
+
+    // The address stays connected to the restarted actor.
+    assert_eq!(addr_a.send(Increment).await.unwrap(), 1);
+    assert_eq!(addr_b.send(Increment).await.unwrap(), 2);
+    assert_eq!(starts_a.load(Ordering::SeqCst), 2);
+    assert_eq!(starts_b.load(Ordering::SeqCst), 1);
+
+    drop((addr_a, addr_b));
+    assert!(handle.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_one_for_all() {
+    let (mailbox_a, addr_a) = ludi::mailbox::<CounterMsg>(8);
+    let (mailbox_b, addr_b) = ludi::mailbox::<CounterMsg>(8);
+    let starts_a = Arc::new(AtomicUsize::new(0));
+    let starts_b = Arc::new(AtomicUsize::new(0));
+
+    let mut supervisor = Supervisor::new(Strategy::OneForAll);
+    supervisor
+        .add(counter(&starts_a), mailbox_a)
+        .add(counter(&starts_b), mailbox_b);
+    tokio::spawn(supervisor.run());
+
+    assert_eq!(addr_b.send(Increment).await.unwrap(), 1);
+
+    addr_a.send(Fail).await.unwrap();
+
+    assert_eq!(addr_b.send(Increment).await.unwrap(), 1);
+    assert_eq!(starts_a.load(Ordering::SeqCst), 2);
+    assert_eq!(starts_b.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_rest_for_one() {
+    let (mailbox_a, addr_a) = ludi::mailbox::<CounterMsg>(8);
+    let (mailbox_b, addr_b) = ludi::mailbox::<CounterMsg>(8);
+    let starts_a = Arc::new(AtomicUsize::new(0));
+    let starts_b = Arc::new(AtomicUsize::new(0));
+
+    let mut supervisor = Supervisor::new(Strategy::RestForOne);
+    supervisor
+        .add(counter(&starts_a), mailbox_a)
+        .add(counter(&starts_b), mailbox_b);
+    tokio::spawn(supervisor.run());
+
+    addr_b.send(Fail).await.unwrap();
+    assert_eq!(addr_a.send(Increment).await.unwrap(), 1);
+    assert_eq!(starts_a.load(Ordering::SeqCst), 1);
+
+    addr_a.send(Fail).await.unwrap();
+    assert_eq!(addr_b.send(Increment).await.unwrap(), 1);
+    assert_eq!(starts_a.load(Ordering::SeqCst), 2);
+    assert_eq!(starts_b.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_restart_intensity() {
+    let (mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    let starts = Arc::new(AtomicUsize::new(0));
+
+    let mut supervisor = Supervisor::new(Strategy::OneForOne)
+        .with_intensity(RestartIntensity::new(2, Duration::from_secs(60)));
+    supervisor.add(counter(&starts), mailbox);
+    let handle = tokio::spawn(supervisor.run());
+
+    for _ in 0..3 {
+        addr.send(Fail).await.unwrap();
+    }
+
+    assert!(handle.await.unwrap().is_err());
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_runner() {
+    let (mailbox, addr) = ludi::mailbox::<CounterMsg>(8);
+    let starts = Arc::new(AtomicUsize::new(0));
+    let failures = Arc::new(AtomicUsize::new(0));
+
+    let mut supervisor = Supervisor::new(Strategy::OneForOne);
+    let count = failures.clone();
+    supervisor.add_with_runner(Runner::new(), counter(&starts), mailbox, move |()| {
+        // Only restart the child after its first failure.
+        count.fetch_add(1, Ordering::SeqCst) == 0
+    });
+    let handle = tokio::spawn(supervisor.run());
+
+    assert!(addr.send(HasAddress).await.unwrap());
+
+    addr.send(Fail).await.unwrap();
+    assert_eq!(addr.send(Increment).await.unwrap(), 1);
+    assert!(addr.send(HasAddress).await.unwrap());
+
+    // The child is not restarted, so the supervisor returns while the address is still held.
+    addr.send(Fail).await.unwrap();
+    assert!(handle.await.unwrap().is_ok());
+    assert_eq!(starts.load(Ordering::SeqCst), 2);
+    assert_eq!(failures.load(Ordering::SeqCst), 2);
+}
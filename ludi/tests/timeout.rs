@@ -0,0 +1,110 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use ludi::{
+    timer::{Sleep, Timer},
+    Actor, Error, Runner,
+};
+
+#[derive(Default, ludi::Controller)]
+pub struct Sleeper;
+
+impl Actor for Sleeper {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+#[ludi::implement]
+#[ctrl(err)]
+#[msg(wrap)]
+impl Sleeper {
+    pub async fn echo(&self, value: u32) -> Result<u32, Error> {
+        Ok(value)
+    }
+
+    #[ctrl(timeout = "50ms")]
+    pub async fn hang(&self) -> Result<(), Error> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn test_message_future_timeout() {
+    let (mut mailbox, addr) = ludi::mailbox::<SleeperMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Sleeper, &mut mailbox).await });
+
+    let res = addr
+        .send_timeout(SleeperMsgEcho { value: 1 }, Duration::from_secs(5))
+        .await;
+    assert_eq!(res, Ok(Ok(1)));
+
+    let res = addr
+        .wait(SleeperMsgHang.into())
+        .timeout(Duration::from_millis(50))
+        .await;
+    assert_eq!(res.err(), Some(Error::Timeout));
+}
+
+#[tokio::test]
+async fn test_ctrl_timeout() {
+    let (mut mailbox, addr) = ludi::mailbox::<SleeperMsg>(8);
+    tokio::spawn(async move { ludi::run(&mut Sleeper, &mut mailbox).await });
+
+    let ctrl = SleeperCtrl::from(addr);
+    assert_eq!(ctrl.echo(2).await, Ok(2));
+    assert_eq!(ctrl.hang().await, Err(Error::Timeout));
+}
+
+/// A timer which expires immediately, and counts the sleeps it created.
+#[derive(Clone, Default)]
+struct ExpiredTimer(Arc<AtomicUsize>);
+
+impl Timer for ExpiredTimer {
+    fn sleep(&self, _duration: Duration) -> Sleep {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        Box::pin(std::future::ready(()))
+    }
+}
+
+#[tokio::test]
+async fn test_runner_timer_timeout() {
+    let timer = ExpiredTimer::default();
+    let runner = Runner::new().with_timer(timer.clone());
+
+    let (mut mailbox, addr) = ludi::mailbox::<SleeperMsg>(8);
+    tokio::spawn(async move { runner.run(&mut Sleeper, &mut mailbox).await });
+
+    // Once the runner has started, its timer is used instead of the default timer.
+    assert_eq!(addr.send(SleeperMsgEcho { value: 1 }).await, Ok(Ok(1)));
+    let res = addr
+        .send_timeout(SleeperMsgHang, Duration::from_secs(60))
+        .await;
+    assert_eq!(res, Err(Error::Timeout));
+    assert_eq!(timer.0.load(Ordering::Relaxed), 1);
+}
+
+// Outside a tokio runtime there is no timer, unless another adapter is enabled.
+#[cfg(not(any(feature = "async-std", feature = "futures-timer")))]
+#[test]
+fn test_timeout_without_timer() {
+    let (mut mailbox, addr) = ludi::mailbox::<SleeperMsg>(8);
+
+    let send = async move {
+        // The response is waited for without a timeout.
+        addr.send_timeout(SleeperMsgEcho { value: 1 }, Duration::from_millis(1))
+            .await
+    };
+    let run = async move { ludi::run(&mut Sleeper, &mut mailbox).await };
+
+    let (res, _) = futures_executor::block_on(futures_util::future::join(send, run));
+    assert_eq!(res, Ok(Ok(1)));
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use ludi::{timer::TokioTimer, Actor, Context, Handler, Runner};
+
+#[derive(ludi::Message)]
+struct Start;
+
+#[derive(ludi::Message)]
+struct Tick;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = usize)]
+struct Count;
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = bool)]
+struct Schedule;
+
+#[derive(ludi::Wrap)]
+enum TickerMsg {
+    Start(Start),
+    Tick(Tick),
+    Count(Count),
+    Schedule(Schedule),
+}
+
+#[derive(Default)]
+struct Ticker {
+    ticks: usize,
+}
+
+impl Actor for Ticker {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Start> for Ticker {
+    async fn handle(&mut self, _msg: Start, ctx: &mut Context<Self>) {
+        let handle = ctx
+            .notify_interval(|| TickerMsg::from(Tick), Duration::from_millis(20))
+            .unwrap();
+        ctx.notify_later(TickerMsg::from(Tick), Duration::from_millis(10))
+            .unwrap();
+        ctx.notify_later(TickerMsg::from(Tick), Duration::from_millis(30))
+            .unwrap()
+            .cancel();
+
+        // Stop the interval after it has fired twice.
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            handle.cancel();
+        });
+    }
+}
+
+impl Handler<Tick> for Ticker {
+    async fn handle(&mut self, _msg: Tick, _ctx: &mut Context<Self>) {
+        self.ticks += 1;
+    }
+}
+
+impl Handler<Schedule> for Ticker {
+    async fn handle(&mut self, _msg: Schedule, ctx: &mut Context<Self>) -> bool {
+        ctx.notify_later(TickerMsg::from(Tick), Duration::from_millis(1))
+            .is_some()
+    }
+}
+
+impl Handler<Count> for Ticker {
+    async fn handle(&mut self, _msg: Count, _ctx: &mut Context<Self>) -> usize {
+        self.ticks
+    }
+}
+
+#[tokio::test]
+async fn test_notify() {
+    let (mut mailbox, addr) = ludi::mailbox::<TickerMsg>(8);
+    let mut actor = Ticker::default();
+
+    tokio::spawn(async move {
+        Runner::new()
+            .with_timer(TokioTimer)
+            .run(&mut actor, &mut mailbox)
+            .await
+    });
+
+    addr.send(Start).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(addr.send(Count).await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_scheduled_does_not_keep_mailbox_open() {
+    let (mut mailbox, addr) = ludi::mailbox::<TickerMsg>(8);
+    let mut actor = Ticker::default();
+
+    let handle = tokio::spawn(async move {
+        Runner::new()
+            .with_timer(TokioTimer)
+            .run(&mut actor, &mut mailbox)
+            .await
+    });
+
+    addr.send(Start).await.unwrap();
+    drop(addr);
+
+    assert!(handle.await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn test_notify_default_timer() {
+    let (mut mailbox, addr) = ludi::mailbox::<TickerMsg>(8);
+    let mut actor = Ticker::default();
+
+    // The runner falls back to the default timer, which uses `tokio`.
+    tokio::spawn(async move { Runner::new().run(&mut actor, &mut mailbox).await });
+
+    assert!(addr.send(Schedule).await.unwrap());
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(addr.send(Count).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_notify_without_address() {
+    let (mut mailbox, addr) = ludi::mailbox::<TickerMsg>(8);
+    let mut actor = Ticker::default();
+
+    tokio::spawn(async move { ludi::run(&mut actor, &mut mailbox).await });
+
+    // The actor does not have an address to its own mailbox.
+    assert!(!addr.send(Schedule).await.unwrap());
+}
+
+#[test]
+fn test_default_timer_outside_runtime() {
+    // The tokio adapter is not used outside a tokio runtime, where it would panic.
+    if let Some(timer) = ludi::timer::default_timer() {
+        drop(timer.sleep(Duration::from_millis(1)));
+    }
+}
@@ -0,0 +1,134 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use ludi::{Actor, Address, Context, Handler};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    Instrument, Subscriber,
+};
+use tracing_subscriber::{layer::Context as LayerContext, prelude::*, registry::LookupSpan, Layer};
+
+#[derive(Debug, PartialEq)]
+struct SpanRecord {
+    name: &'static str,
+    parent: Option<&'static str>,
+    fields: Vec<(String, String)>,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<SpanRecord>>>);
+
+struct Fields(Vec<(String, String)>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let mut fields = Fields(Vec::new());
+        attrs.record(&mut fields);
+
+        let span = ctx.span(id).unwrap();
+        self.0.lock().unwrap().push(SpanRecord {
+            name: span.name(),
+            parent: span.parent().map(|parent| parent.name()),
+            fields: fields.0,
+        });
+    }
+}
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Ping(u32);
+
+struct Echo;
+
+impl Actor for Echo {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Ping> for Echo {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        tracing::debug_span!("echo").in_scope(|| msg.0)
+    }
+}
+
+struct Forward(Address<Ping>);
+
+impl Actor for Forward {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Ping> for Forward {
+    async fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) -> u32 {
+        self.0.send(msg).await.unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_dispatch_span() {
+    let recorder = Recorder::default();
+    let _guard = tracing_subscriber::registry()
+        .with(recorder.clone())
+        .set_default();
+
+    let (mut echo_mailbox, echo_addr) = ludi::mailbox::<Ping>(8);
+    let (mut forward_mailbox, forward_addr) = ludi::mailbox::<Ping>(8);
+    tokio::spawn(async move { ludi::run(&mut Echo, &mut echo_mailbox).await });
+    tokio::spawn(async move { ludi::run(&mut Forward(echo_addr), &mut forward_mailbox).await });
+
+    let response = forward_addr
+        .send(Ping(1))
+        .instrument(tracing::info_span!("request"))
+        .await;
+    assert_eq!(response, Ok(1));
+
+    let spans = recorder.0.lock().unwrap();
+    let names = spans
+        .iter()
+        .map(|span| (span.name, span.parent))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("request", None),
+            ("dispatch", Some("request")),
+            ("dispatch", Some("dispatch")),
+            ("echo", Some("dispatch")),
+        ]
+    );
+
+    let fields = &spans[1].fields;
+    assert!(fields.contains(&(
+        "actor".to_string(),
+        std::any::type_name::<Forward>().to_string()
+    )));
+    assert!(fields.contains(&(
+        "message".to_string(),
+        std::any::type_name::<Ping>().to_string()
+    )));
+    assert!(fields.contains(&("wants_response".to_string(), "true".to_string())));
+}
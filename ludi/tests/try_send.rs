@@ -0,0 +1,60 @@
+use ludi::{Actor, Context, Handler, TrySendError};
+
+#[derive(Debug, PartialEq, ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Double(u32);
+
+struct Doubler;
+
+impl Actor for Doubler {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Double> for Doubler {
+    async fn handle(&mut self, msg: Double, _ctx: &mut Context<Self>) -> u32 {
+        msg.0 * 2
+    }
+}
+
+#[tokio::test]
+async fn test_try_queue_full() {
+    let (_mailbox, mut addr) = ludi::mailbox::<Double>(1);
+
+    // The address is guaranteed one slot in addition to the capacity.
+    addr.try_queue(Double(1)).unwrap();
+    addr.try_queue(Double(2)).unwrap();
+
+    let err = addr.try_queue(Double(3)).unwrap_err();
+    assert!(err.is_full());
+    assert_eq!(err.into_inner(), Double(3));
+}
+
+#[tokio::test]
+async fn test_try_queue_disconnected() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<Double>(1);
+
+    mailbox.close();
+    assert!(matches!(
+        addr.try_queue(Double(1)),
+        Err(TrySendError::Stopped(Double(1)))
+    ));
+
+    drop(mailbox);
+    let err = addr.try_send(Double(2)).unwrap_err();
+    assert!(err.is_disconnected());
+    assert_eq!(err.into_inner(), Double(2));
+}
+
+#[tokio::test]
+async fn test_try_send() {
+    let (mut mailbox, mut addr) = ludi::mailbox::<Double>(8);
+    tokio::spawn(async move { ludi::run(&mut Doubler, &mut mailbox).await });
+
+    let response = addr.try_send(Double(2)).unwrap();
+    assert_eq!(response.await.unwrap(), 4);
+}
@@ -0,0 +1,48 @@
+use ludi::{Actor, Context, Handler};
+
+#[derive(ludi::Message)]
+#[ludi(return_ty = u32)]
+struct Double(u32);
+
+struct Doubler;
+
+impl Actor for Doubler {
+    type Stop = ();
+    type Error = ();
+
+    async fn stopped(&mut self) -> Result<Self::Stop, Self::Error> {
+        Ok(())
+    }
+}
+
+impl Handler<Double> for Doubler {
+    async fn handle(&mut self, msg: Double, _ctx: &mut Context<Self>) -> u32 {
+        msg.0 * 2
+    }
+}
+
+#[tokio::test]
+async fn test_weak_address_upgrade() {
+    let (mut mailbox, addr) = ludi::mailbox::<Double>(8);
+    tokio::spawn(async move { ludi::run(&mut Doubler, &mut mailbox).await });
+
+    let weak = addr.downgrade();
+    assert_eq!(weak.strong_count(), 1);
+
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(weak.strong_count(), 2);
+    assert_eq!(upgraded.send(Double(2)).await, Ok(4));
+}
+
+#[tokio::test]
+async fn test_weak_address_does_not_keep_mailbox_open() {
+    let (mut mailbox, addr) = ludi::mailbox::<Double>(8);
+    let handle = tokio::spawn(async move { ludi::run(&mut Doubler, &mut mailbox).await });
+
+    let weak = addr.downgrade();
+    drop(addr);
+
+    handle.await.unwrap().unwrap();
+    assert_eq!(weak.strong_count(), 0);
+    assert!(weak.upgrade().is_none());
+}